@@ -4,6 +4,7 @@ use std::error::Error;
 pub enum CompileError {
     TypeCheck(fmm::analysis::type_check::TypeCheckError),
     Name(fmm::analysis::name::NameError),
+    TailCall(fmm::analysis::tail_call::TailCallError),
 }
 
 impl Error for CompileError {}
@@ -13,6 +14,7 @@ impl std::fmt::Display for CompileError {
         match self {
             Self::TypeCheck(error) => write!(formatter, "{}", error),
             Self::Name(error) => write!(formatter, "{}", error),
+            Self::TailCall(error) => write!(formatter, "{}", error),
         }
     }
 }
@@ -28,3 +30,9 @@ impl From<fmm::analysis::name::NameError> for CompileError {
         Self::Name(error)
     }
 }
+
+impl From<fmm::analysis::tail_call::TailCallError> for CompileError {
+    fn from(error: fmm::analysis::tail_call::TailCallError) -> Self {
+        Self::TailCall(error)
+    }
+}
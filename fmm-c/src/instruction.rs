@@ -8,16 +8,22 @@ pub fn compile_block(
     global_variables: &FnvHashSet<String>,
     type_ids: &FnvHashMap<fmm::types::Type, String>,
 ) -> String {
-    block
-        .instructions()
+    let tail_call = fmm::analysis::tail_call::find_must_tail_call(block);
+    let instructions = block.instructions();
+
+    instructions[..instructions.len() - tail_call.iter().count()]
         .iter()
         .map(|instruction| compile_instruction(instruction, global_variables, type_ids))
-        .chain([compile_terminal_instruction(
-            block.terminal_instruction(),
-            branch_variable_name,
-            global_variables,
-            type_ids,
-        )])
+        .chain([if let Some(call) = tail_call {
+            compile_must_tail_call(call, global_variables, type_ids)
+        } else {
+            compile_terminal_instruction(
+                block.terminal_instruction(),
+                branch_variable_name,
+                global_variables,
+                type_ids,
+            )
+        }])
         .map(|string| "".to_owned() + &string)
         .collect::<Vec<_>>()
         .join("\n")
@@ -43,16 +49,27 @@ fn compile_instruction(
         }
         Instruction::AllocateStack(allocate) => {
             let entity_name = allocate.name().to_owned() + "_entity";
+            let pointer_type = fmm::types::Pointer::new(allocate.type_().clone()).into();
+            let pointer_name = compile_typed_name(&pointer_type, allocate.name());
 
-            format!(
-                "{};{}=&{};",
-                compile_typed_name(allocate.type_(), &entity_name),
-                compile_typed_name(
-                    &fmm::types::Pointer::new(allocate.type_().clone()).into(),
-                    allocate.name(),
-                ),
-                entity_name,
-            )
+            if let Some(count) = allocate.count() {
+                format!(
+                    "{};{}={};",
+                    compile_typed_name(
+                        allocate.type_(),
+                        &format!("{}[{}]", entity_name, compile_expression(count)),
+                    ),
+                    pointer_name,
+                    entity_name,
+                )
+            } else {
+                format!(
+                    "{};{}=&{};",
+                    compile_typed_name(allocate.type_(), &entity_name),
+                    pointer_name,
+                    entity_name,
+                )
+            }
         }
         Instruction::AtomicLoad(load) => format!(
             "{}=({})atomic_load_explicit(({}){},{});",
@@ -63,8 +80,12 @@ fn compile_instruction(
             compile_atomic_ordering(load.ordering()),
         ),
         Instruction::AtomicOperation(operation) => format!(
-            "{}=atomic_fetch_{}_explicit(({}){},{},{});",
-            compile_typed_name(&operation.type_().into(), operation.name()),
+            "{}atomic_fetch_{}_explicit(({}){},{},{});",
+            if operation.is_result_discarded() {
+                "".into()
+            } else {
+                compile_typed_name(&operation.type_().into(), operation.name()) + "="
+            },
             match operation.operator() {
                 AtomicOperator::Add => "add",
                 AtomicOperator::Subtract => "sub",
@@ -141,7 +162,17 @@ fn compile_instruction(
             format!(
                 "{};if({}){{\n{}\n}}else{{\n{}\n}}",
                 compile_typed_name(&if_.type_().clone(), if_.name()),
-                compile_expression(if_.condition()),
+                match if_.likely() {
+                    Some(branch) => format!(
+                        "__builtin_expect({},{})",
+                        compile_expression(if_.condition()),
+                        match branch {
+                            IfBranch::Then => 1,
+                            IfBranch::Else => 0,
+                        }
+                    ),
+                    None => compile_expression(if_.condition()),
+                },
                 compile_block(if_.then()),
                 compile_block(if_.else_())
             )
@@ -149,7 +180,15 @@ fn compile_instruction(
         Instruction::Load(load) => format!(
             "{}=*{};",
             compile_typed_name(load.type_(), load.name()),
-            compile_expression(load.pointer()),
+            match load.alignment() {
+                Some(alignment) => format!(
+                    "({})__builtin_assume_aligned({},{})",
+                    compile_type_id(&fmm::types::Pointer::new(load.type_().clone()).into()),
+                    compile_expression(load.pointer()),
+                    alignment,
+                ),
+                None => compile_expression(load.pointer()),
+            },
         ),
         Instruction::MemoryCopy(copy) => format!(
             "memcpy({},{},{});",
@@ -173,6 +212,27 @@ fn compile_instruction(
     }
 }
 
+// Calls and returns are merged as clang requires them to be in single
+// statements.
+fn compile_must_tail_call(
+    call: &Call,
+    global_variables: &FnvHashSet<String>,
+    type_ids: &FnvHashMap<fmm::types::Type, String>,
+) -> String {
+    let compile_expression =
+        |expression| expression::compile(expression, global_variables, type_ids);
+
+    format!(
+        "__attribute__((musttail)) return {}({});",
+        compile_expression(call.function()),
+        call.arguments()
+            .iter()
+            .map(compile_expression)
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
 fn compile_terminal_instruction(
     instruction: &TerminalInstruction,
     block_variable_name: Option<&str>,
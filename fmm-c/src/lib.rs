@@ -20,10 +20,15 @@ const INCLUDES: &[&str] = &[
     "#include <string.h>",
 ];
 
+// A word size is the one of targets of C compilers to compile generated code
+// which decides if signatures of must-tail calls are compatible.
 pub fn compile(
     module: &Module,
     instruction_configuration: Option<InstructionConfiguration>,
+    word_bytes: usize,
 ) -> Result<String, CompileError> {
+    fmm::analysis::tail_call::check(module, word_bytes)?;
+
     let module = rename::rename(module);
     let global_variables = module
         .variable_declarations()
@@ -215,6 +220,11 @@ fn compile_function_definition(
 ) -> String {
     // TODO Support argument options.
     compile_linkage(definition.options().linkage()).to_owned()
+        + &definition
+            .options()
+            .alignment()
+            .map(|alignment| format!("__attribute__((aligned({}))) ", alignment))
+            .unwrap_or_default()
         + &type_::compile_name(
             definition.result_type(),
             &format!(
@@ -292,14 +302,26 @@ mod tests {
     use fmm::types::{self, CallingConvention, Type};
     use pretty_assertions::assert_eq;
 
+    const WORD_BYTES: usize = 8;
+
     fn compile_final_module(module: &Module) {
         fmm::analysis::validation::validate(module).unwrap();
 
         let directory = tempfile::tempdir().unwrap();
         let file_path = directory.path().join("foo.c");
-        let source = compile(module, Some(DUMMY_INSTRUCTION_CONFIGURATION.clone())).unwrap();
+        let source = compile(
+            module,
+            Some(DUMMY_INSTRUCTION_CONFIGURATION.clone()),
+            WORD_BYTES,
+        )
+        .unwrap();
 
-        let other = compile(module, Some(DUMMY_INSTRUCTION_CONFIGURATION.clone())).unwrap();
+        let other = compile(
+            module,
+            Some(DUMMY_INSTRUCTION_CONFIGURATION.clone()),
+            WORD_BYTES,
+        )
+        .unwrap();
         assert_eq!(source, other);
 
         println!("{}", source);
@@ -1241,6 +1263,27 @@ mod tests {
             ));
         }
 
+        #[test]
+        fn compile_allocate_stack_array() {
+            compile_function_definition(create_function_definition(
+                "f",
+                vec![Argument::new("x", types::Primitive::PointerInteger)],
+                types::Pointer::new(types::Primitive::Integer64),
+                Block::new(
+                    vec![AllocateStack::array(
+                        types::Primitive::Integer64,
+                        Variable::new("x"),
+                        "y",
+                    )
+                    .into()],
+                    Return::new(
+                        types::Pointer::new(types::Primitive::Integer64),
+                        Variable::new("y"),
+                    ),
+                ),
+            ));
+        }
+
         #[test]
         fn compile_atomic_load() {
             compile_function_definition(create_function_definition(
@@ -1628,4 +1671,96 @@ mod tests {
             ));
         }
     }
+
+    mod tail_call {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn create_module(instructions: Vec<Instruction>, result: Expression) -> Module {
+            let function_type = create_function_type(vec![], types::Primitive::PointerInteger);
+
+            Module::new(
+                vec![],
+                vec![FunctionDeclaration::new("g", function_type)],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    Block::new(
+                        instructions,
+                        Return::new(types::Primitive::PointerInteger, result),
+                    ),
+                )],
+            )
+        }
+
+        fn create_call() -> Instruction {
+            Call::new(
+                create_function_type(vec![], types::Primitive::PointerInteger),
+                Variable::new("g"),
+                vec![],
+                "x",
+            )
+            .set_tail_call(TailCall::MustTail)
+            .into()
+        }
+
+        #[test]
+        fn compile_must_tail_call() {
+            compile_final_module(&create_module(
+                vec![create_call()],
+                Variable::new("x").into(),
+            ));
+        }
+
+        #[test]
+        fn fail_to_compile_must_tail_call_not_in_tail_position() {
+            assert_eq!(
+                compile(
+                    &create_module(vec![create_call()], Primitive::PointerInteger(42).into()),
+                    None,
+                    WORD_BYTES
+                ),
+                Err(CompileError::TailCall(
+                    fmm::analysis::tail_call::TailCallError::NotInTailPosition("x".into())
+                ))
+            );
+        }
+
+        #[test]
+        fn fail_to_compile_must_tail_call_with_incompatible_signature() {
+            let function_type = types::Function::new(
+                vec![],
+                types::Primitive::PointerInteger,
+                CallingConvention::Target,
+            );
+
+            assert_eq!(
+                compile(
+                    &Module::new(
+                        vec![],
+                        vec![FunctionDeclaration::new("g", function_type.clone())],
+                        vec![],
+                        vec![create_function_definition(
+                            "f",
+                            vec![],
+                            types::Primitive::PointerInteger,
+                            Block::new(
+                                vec![Call::new(function_type, Variable::new("g"), vec![], "x")
+                                    .set_tail_call(TailCall::MustTail)
+                                    .into()],
+                                Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+                            ),
+                        )],
+                    ),
+                    None,
+                    WORD_BYTES
+                ),
+                Err(CompileError::TailCall(
+                    fmm::analysis::tail_call::TailCallError::IncompatibleSignature("x".into())
+                ))
+            );
+        }
+    }
 }
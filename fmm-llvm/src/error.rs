@@ -3,9 +3,11 @@ use std::error::Error;
 #[derive(Clone, Debug, PartialEq)]
 pub enum CompileError {
     Llvm(String),
+    MustTailCall(String),
     TargetMachineNotCreated,
     TypeCheck(fmm::analysis::type_check::TypeCheckError),
     Name(fmm::analysis::name::NameError),
+    TailCall(fmm::analysis::tail_call::TailCallError),
 }
 
 impl Error for CompileError {}
@@ -16,11 +18,15 @@ impl std::fmt::Display for CompileError {
             Self::Llvm(string) => {
                 write!(formatter, "{}", string)
             }
+            Self::MustTailCall(name) => {
+                write!(formatter, "must-tail call not supported: {}", name)
+            }
             Self::TargetMachineNotCreated => {
                 write!(formatter, "failed to create target machine")
             }
             Self::TypeCheck(error) => write!(formatter, "{}", error),
             Self::Name(error) => write!(formatter, "{}", error),
+            Self::TailCall(error) => write!(formatter, "{}", error),
         }
     }
 }
@@ -48,3 +54,9 @@ impl From<fmm::analysis::name::NameError> for CompileError {
         Self::Name(error)
     }
 }
+
+impl From<fmm::analysis::tail_call::TailCallError> for CompileError {
+    fn from(error: fmm::analysis::tail_call::TailCallError) -> Self {
+        Self::TailCall(error)
+    }
+}
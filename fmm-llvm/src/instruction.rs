@@ -64,9 +64,16 @@ fn compile_instruction<'c, 'a>(
                 .unwrap(),
         ),
         Instruction::AllocateStack(allocate) => Some(
-            builder
-                .build_alloca(compile_type(allocate.type_()), allocate.name())
-                .into(),
+            if let Some(count) = allocate.count() {
+                builder.build_array_alloca(
+                    compile_type(allocate.type_()),
+                    compile_expression(count).into_int_value(),
+                    allocate.name(),
+                )
+            } else {
+                builder.build_alloca(compile_type(allocate.type_()), allocate.name())
+            }
+            .into(),
         ),
         Instruction::AtomicLoad(load) => {
             let value = builder.build_load(
@@ -106,6 +113,13 @@ fn compile_instruction<'c, 'a>(
             None
         }
         Instruction::Call(call) => {
+            // The LLVM C API of LLVM 16 can mark calls only as `tail` and not
+            // `musttail`. Must-tail calls are rejected rather than compiled into
+            // ordinary tail calls which LLVM is free not to optimize.
+            if call.tail_call() == TailCall::MustTail {
+                return Err(CompileError::MustTailCall(call.name().into()));
+            }
+
             let value = builder.build_indirect_call(
                 type_::compile_function(context, call.type_()),
                 compile_expression(call.function()).into_pointer_value(),
@@ -229,11 +243,22 @@ fn compile_instruction<'c, 'a>(
                 Some(phi.as_basic_value())
             }
         }
-        Instruction::Load(load) => Some(builder.build_load(
-            compile_type(load.type_()),
-            compile_expression(load.pointer()).into_pointer_value(),
-            load.name(),
-        )),
+        Instruction::Load(load) => {
+            let value = builder.build_load(
+                compile_type(load.type_()),
+                compile_expression(load.pointer()).into_pointer_value(),
+                load.name(),
+            );
+
+            if let Some(alignment) = load.alignment() {
+                value
+                    .as_instruction_value()
+                    .unwrap()
+                    .set_alignment(alignment as u32)?;
+            }
+
+            Some(value)
+        }
         Instruction::MemoryCopy(copy) => {
             builder.build_memcpy(
                 compile_expression(copy.destination()).into_pointer_value(),
@@ -74,6 +74,11 @@ fn compile_module<'c>(
     context: &Context<'c>,
     module: &Module,
 ) -> Result<inkwell::module::Module<'c>, CompileError> {
+    fmm::analysis::tail_call::check(
+        module,
+        context.target_data().get_pointer_byte_size(None) as usize,
+    )?;
+
     let llvm_module = context.inkwell().create_module("");
     llvm_module.set_triple(&context.target_machine().get_triple());
 
@@ -264,6 +269,10 @@ fn declare_function_definition<'c>(
             definition.options().is_address_named(),
         ));
 
+    if let Some(alignment) = definition.options().alignment() {
+        function.as_global_value().set_alignment(alignment as u32);
+    }
+
     // spell-checker: disable-next-line
     for attribute in ["willreturn", "nounwind"] {
         function.add_attribute(
@@ -1489,6 +1498,77 @@ mod tests {
             ));
         }
 
+        #[test]
+        fn fail_to_compile_must_tail_call() {
+            let function_type = types::Function::new(
+                vec![],
+                types::Primitive::PointerInteger,
+                CallingConvention::Source,
+            );
+
+            assert_eq!(
+                compile_to_object(
+                    &Module::new(
+                        vec![],
+                        vec![FunctionDeclaration::new("g", function_type.clone())],
+                        vec![],
+                        vec![create_function_definition(
+                            "f",
+                            vec![],
+                            types::Primitive::PointerInteger,
+                            Block::new(
+                                vec![Call::new(function_type, Variable::new("g"), vec![], "x")
+                                    .set_tail_call(TailCall::MustTail)
+                                    .into()],
+                                Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+                            ),
+                        )],
+                    ),
+                    &DUMMY_INSTRUCTION_CONFIGURATION,
+                    None,
+                ),
+                Err(CompileError::MustTailCall("x".into()))
+            );
+        }
+
+        #[test]
+        fn fail_to_compile_must_tail_call_not_in_tail_position() {
+            let function_type = types::Function::new(
+                vec![],
+                types::Primitive::PointerInteger,
+                CallingConvention::Source,
+            );
+
+            assert_eq!(
+                compile_to_object(
+                    &Module::new(
+                        vec![],
+                        vec![FunctionDeclaration::new("g", function_type.clone())],
+                        vec![],
+                        vec![create_function_definition(
+                            "f",
+                            vec![],
+                            types::Primitive::PointerInteger,
+                            Block::new(
+                                vec![Call::new(function_type, Variable::new("g"), vec![], "x")
+                                    .set_tail_call(TailCall::MustTail)
+                                    .into()],
+                                Return::new(
+                                    types::Primitive::PointerInteger,
+                                    Primitive::PointerInteger(42)
+                                ),
+                            ),
+                        )],
+                    ),
+                    &DUMMY_INSTRUCTION_CONFIGURATION,
+                    None,
+                ),
+                Err(CompileError::TailCall(
+                    fmm::analysis::tail_call::TailCallError::NotInTailPosition("x".into())
+                ))
+            );
+        }
+
         #[test]
         fn compile_unreachable() {
             compile_function_definition(create_function_definition(
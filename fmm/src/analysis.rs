@@ -1,13 +1,39 @@
+pub mod alias;
+pub mod allocation_elimination;
+pub mod bit_cast_folding;
+pub mod branch_hoisting;
 pub mod c_calling_convention;
+pub mod const_eval;
+pub mod cost;
 pub mod cps;
 pub mod expression_conversion;
 pub mod format;
+mod global_name;
+pub mod icf;
+pub mod if_simplify;
+pub mod leaf_function;
 mod local_variable;
+pub mod merge;
 pub mod name;
+pub mod outline;
+pub mod pass_manager;
+pub mod pure_call_elimination;
+pub mod reference_count;
 pub mod rename;
+pub mod retain;
+pub mod small_alloc;
+pub mod specialize;
+pub mod stack_escape;
+pub mod store_forwarding;
+pub mod tail_call;
 pub mod type_check;
 pub mod type_collection;
 pub mod type_conversion;
 pub mod type_size;
+pub mod undef_propagation;
+pub mod undefined_value;
+pub mod union_deconstruction;
+pub mod unreachable_code;
 pub mod validation;
 pub mod variable_scope;
+mod variable_use;
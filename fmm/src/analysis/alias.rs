@@ -0,0 +1,217 @@
+use super::variable_use;
+use crate::ir::*;
+use fnv::{FnvHashMap, FnvHashSet};
+
+// Returns false only when pointers are derived from distinct allocations which
+// do not escape from a function.
+pub fn may_alias(definition: &FunctionDefinition, one: &Expression, other: &Expression) -> bool {
+    match (find_base(one), find_base(other)) {
+        (Some(one), Some(other)) if one != other => {
+            let names = collect_non_escaping_allocations(definition);
+
+            !names.contains(one) || !names.contains(other)
+        }
+        _ => true,
+    }
+}
+
+pub(crate) fn find_base(pointer: &Expression) -> Option<&str> {
+    match pointer {
+        Expression::BitCast(bit_cast) => find_base(bit_cast.expression()),
+        Expression::PointerAddress(address) => find_base(address.pointer()),
+        Expression::RecordAddress(address) => find_base(address.pointer()),
+        Expression::UnionAddress(address) => find_base(address.pointer()),
+        Expression::Variable(variable) => Some(variable.name()),
+        _ => None,
+    }
+}
+
+pub(crate) fn collect_non_escaping_allocations(
+    definition: &FunctionDefinition,
+) -> FnvHashSet<&str> {
+    let counts = variable_use::count(definition);
+    let mut allocated_names = FnvHashSet::default();
+    let mut pointer_counts = FnvHashMap::default();
+
+    collect_from_block(definition.body(), &mut allocated_names, &mut pointer_counts);
+
+    allocated_names
+        .into_iter()
+        .filter(|&name| counts.get(name) == pointer_counts.get(name))
+        .collect()
+}
+
+fn collect_from_block<'a>(
+    block: &'a Block,
+    allocated_names: &mut FnvHashSet<&'a str>,
+    pointer_counts: &mut FnvHashMap<&'a str, usize>,
+) {
+    for instruction in block.instructions() {
+        let pointers = match instruction {
+            Instruction::AllocateHeap(allocate) => {
+                allocated_names.insert(allocate.name());
+                vec![]
+            }
+            Instruction::AllocateStack(allocate) => {
+                allocated_names.insert(allocate.name());
+                vec![]
+            }
+            Instruction::AtomicLoad(load) => vec![load.pointer()],
+            Instruction::AtomicOperation(operation) => vec![operation.pointer()],
+            Instruction::AtomicStore(store) => vec![store.pointer()],
+            Instruction::CompareAndSwap(cas) => vec![cas.pointer()],
+            Instruction::FreeHeap(free) => vec![free.pointer()],
+            Instruction::If(if_) => {
+                collect_from_block(if_.then(), allocated_names, pointer_counts);
+                collect_from_block(if_.else_(), allocated_names, pointer_counts);
+                vec![]
+            }
+            Instruction::Load(load) => vec![load.pointer()],
+            Instruction::MemoryCopy(copy) => vec![copy.source(), copy.destination()],
+            Instruction::Store(store) => vec![store.pointer()],
+            Instruction::Call(_)
+            | Instruction::DeconstructRecord(_)
+            | Instruction::DeconstructUnion(_)
+            | Instruction::Fence(_)
+            | Instruction::ReallocateHeap(_) => vec![],
+        };
+
+        for pointer in pointers {
+            if let Some(name) = find_base(pointer) {
+                *pointer_counts.entry(name).or_default() += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{self, void_type};
+
+    fn create_function_definition(instructions: Vec<Instruction>) -> FunctionDefinition {
+        FunctionDefinition::new(
+            "f",
+            vec![],
+            void_type(),
+            Block::new(instructions, Return::new(void_type(), void_value())),
+            Default::default(),
+        )
+    }
+
+    fn create_store(pointer: impl Into<Expression>) -> Instruction {
+        Store::new(
+            types::Primitive::PointerInteger,
+            Primitive::PointerInteger(42),
+            pointer,
+        )
+        .into()
+    }
+
+    #[test]
+    fn check_distinct_stack_allocations() {
+        let definition = create_function_definition(vec![
+            AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+            AllocateStack::new(types::Primitive::PointerInteger, "y").into(),
+            create_store(Variable::new("x")),
+            create_store(Variable::new("y")),
+        ]);
+
+        assert!(!may_alias(
+            &definition,
+            &Variable::new("x").into(),
+            &Variable::new("y").into()
+        ));
+    }
+
+    #[test]
+    fn check_same_stack_allocation() {
+        let record_type = types::Record::new(vec![
+            types::Primitive::PointerInteger.into(),
+            types::Primitive::PointerInteger.into(),
+        ]);
+        let definition = create_function_definition(vec![
+            AllocateStack::new(record_type.clone(), "x").into(),
+            create_store(RecordAddress::new(
+                record_type.clone(),
+                Variable::new("x"),
+                0,
+            )),
+        ]);
+
+        assert!(may_alias(
+            &definition,
+            &RecordAddress::new(record_type.clone(), Variable::new("x"), 0).into(),
+            &RecordAddress::new(record_type, Variable::new("x"), 1).into(),
+        ));
+    }
+
+    #[test]
+    fn check_fields_of_distinct_heap_allocations() {
+        let record_type = types::Record::new(vec![types::Primitive::PointerInteger.into()]);
+        let definition = create_function_definition(vec![
+            AllocateHeap::new(Primitive::PointerInteger(8), "x").into(),
+            AllocateHeap::new(Primitive::PointerInteger(8), "y").into(),
+            create_store(RecordAddress::new(
+                record_type.clone(),
+                Variable::new("x"),
+                0,
+            )),
+            FreeHeap::new(Variable::new("x")).into(),
+            FreeHeap::new(Variable::new("y")).into(),
+        ]);
+
+        assert!(!may_alias(
+            &definition,
+            &RecordAddress::new(record_type, Variable::new("x"), 0).into(),
+            &Variable::new("y").into(),
+        ));
+    }
+
+    #[test]
+    fn check_escaped_stack_allocation() {
+        let function_type = types::Function::new(
+            vec![types::Pointer::new(types::Primitive::PointerInteger).into()],
+            void_type(),
+            types::CallingConvention::Target,
+        );
+        let definition = create_function_definition(vec![
+            AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+            AllocateStack::new(types::Primitive::PointerInteger, "y").into(),
+            Call::new(
+                function_type,
+                Variable::new("g"),
+                vec![Variable::new("x").into()],
+                "z",
+            )
+            .into(),
+        ]);
+
+        assert!(may_alias(
+            &definition,
+            &Variable::new("x").into(),
+            &Variable::new("y").into()
+        ));
+    }
+
+    #[test]
+    fn check_call_results() {
+        let function_type = types::Function::new(
+            vec![],
+            types::Pointer::new(types::Primitive::PointerInteger),
+            types::CallingConvention::Target,
+        );
+        let definition = create_function_definition(vec![
+            Call::new(function_type.clone(), Variable::new("g"), vec![], "x").into(),
+            Call::new(function_type, Variable::new("g"), vec![], "y").into(),
+            create_store(Variable::new("x")),
+            create_store(Variable::new("y")),
+        ]);
+
+        assert!(may_alias(
+            &definition,
+            &Variable::new("x").into(),
+            &Variable::new("y").into()
+        ));
+    }
+}
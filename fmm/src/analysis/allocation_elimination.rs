@@ -0,0 +1,193 @@
+use super::variable_use;
+use crate::ir::*;
+use fnv::FnvHashSet;
+
+// Remove heap allocations whose only use is to be freed.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_function_definition(definition);
+    }
+}
+
+fn transform_function_definition(definition: &mut FunctionDefinition) {
+    let counts = variable_use::count(definition);
+    let mut allocated_names = FnvHashSet::default();
+    let mut freed_names = FnvHashSet::default();
+
+    collect_names(definition.body(), &mut allocated_names, &mut freed_names);
+
+    let names = allocated_names
+        .intersection(&freed_names)
+        .filter(|&name| counts.get(name.as_str()) == Some(&1))
+        .cloned()
+        .collect::<FnvHashSet<_>>();
+
+    if names.is_empty() {
+        return;
+    }
+
+    transform_block(definition.body_mut(), &names);
+}
+
+fn collect_names(
+    block: &Block,
+    allocated_names: &mut FnvHashSet<String>,
+    freed_names: &mut FnvHashSet<String>,
+) {
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::AllocateHeap(allocate) => {
+                allocated_names.insert(allocate.name().into());
+            }
+            Instruction::FreeHeap(free) => {
+                if let Expression::Variable(variable) = free.pointer() {
+                    freed_names.insert(variable.name().into());
+                }
+            }
+            Instruction::If(if_) => {
+                collect_names(if_.then(), allocated_names, freed_names);
+                collect_names(if_.else_(), allocated_names, freed_names);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn transform_block(block: &mut Block, names: &FnvHashSet<String>) {
+    block
+        .instructions_mut()
+        .retain(|instruction| match instruction {
+            Instruction::AllocateHeap(allocate) => !names.contains(allocate.name()),
+            Instruction::FreeHeap(free) => !matches!(
+                free.pointer(),
+                Expression::Variable(variable) if names.contains(variable.name())
+            ),
+            _ => true,
+        });
+
+    for instruction in block.instructions_mut() {
+        if let Instruction::If(if_) = instruction {
+            transform_block(if_.then_mut(), names);
+            transform_block(if_.else_mut(), names);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{self, void_type};
+    use pretty_assertions::assert_eq;
+
+    fn create_module(instructions: Vec<Instruction>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                void_type(),
+                Block::new(instructions, Return::new(void_type(), void_value())),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        transform(&mut module);
+
+        module
+    }
+
+    #[test]
+    fn remove_allocation_freed_immediately() {
+        assert_eq!(
+            transform_module(create_module(vec![
+                AllocateHeap::new(Primitive::PointerInteger(42), "x").into(),
+                FreeHeap::new(Variable::new("x")).into(),
+            ])),
+            create_module(vec![])
+        );
+    }
+
+    #[test]
+    fn remove_allocation_freed_in_nested_block() {
+        assert_eq!(
+            transform_module(create_module(vec![
+                AllocateHeap::new(Primitive::PointerInteger(42), "x").into(),
+                If::new(
+                    void_type(),
+                    Primitive::Boolean(true),
+                    Block::new(
+                        vec![FreeHeap::new(Variable::new("x")).into()],
+                        Branch::new(void_type(), void_value()),
+                    ),
+                    Block::new(vec![], Branch::new(void_type(), void_value())),
+                    "y",
+                )
+                .into(),
+            ])),
+            create_module(vec![If::new(
+                void_type(),
+                Primitive::Boolean(true),
+                Block::new(vec![], Branch::new(void_type(), void_value())),
+                Block::new(vec![], Branch::new(void_type(), void_value())),
+                "y",
+            )
+            .into()])
+        );
+    }
+
+    #[test]
+    fn keep_allocation_stored_before_free() {
+        let module = create_module(vec![
+            AllocateHeap::new(Primitive::PointerInteger(8), "x").into(),
+            Store::new(
+                types::Primitive::Integer8,
+                Primitive::Integer8(42),
+                Variable::new("x"),
+            )
+            .into(),
+            FreeHeap::new(Variable::new("x")).into(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_passed_to_call() {
+        let function_type = types::Function::new(
+            vec![types::generic_pointer_type()],
+            void_type(),
+            types::CallingConvention::Target,
+        );
+        let module = Module::new(
+            vec![],
+            vec![FunctionDeclaration::new("g", function_type.clone())],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                void_type(),
+                Block::new(
+                    vec![
+                        AllocateHeap::new(Primitive::PointerInteger(8), "x").into(),
+                        Call::new(
+                            function_type,
+                            Variable::new("g"),
+                            vec![Variable::new("x").into()],
+                            "y",
+                        )
+                        .into(),
+                        FreeHeap::new(Variable::new("x")).into(),
+                    ],
+                    Return::new(void_type(), void_value()),
+                ),
+                Default::default(),
+            )],
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -0,0 +1,125 @@
+use super::expression_conversion;
+use crate::ir::*;
+
+// Collapse chains of bit casts into single ones and remove bit casts between
+// the same types.
+pub fn transform(module: &mut Module) {
+    expression_conversion::convert(module, &fold);
+}
+
+// Inner bit casts are already folded because expressions are converted
+// bottom-up.
+fn fold(expression: &Expression) -> Expression {
+    match expression {
+        Expression::BitCast(bit_cast) => {
+            let (from, value) = match bit_cast.expression() {
+                Expression::BitCast(inner) => (inner.from(), inner.expression()),
+                value => (bit_cast.from(), value),
+            };
+
+            if from == bit_cast.to() {
+                value.clone()
+            } else {
+                BitCast::new(from.clone(), bit_cast.to().clone(), value.clone()).into()
+            }
+        }
+        _ => expression.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::type_check,
+        types::{self, Type},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn create_module(result_type: impl Into<Type>, result: impl Into<Expression>) -> Module {
+        let result_type = result_type.into();
+
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new("x", types::Primitive::Integer64)],
+                result_type.clone(),
+                Block::new(vec![], Return::new(result_type, result)),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn collapse_bit_cast_chain() {
+        assert_eq!(
+            transform_module(create_module(
+                types::Primitive::Float64,
+                BitCast::new(
+                    types::generic_pointer_type(),
+                    types::Primitive::Float64,
+                    BitCast::new(
+                        types::Primitive::Integer64,
+                        types::generic_pointer_type(),
+                        Variable::new("x"),
+                    ),
+                ),
+            )),
+            create_module(
+                types::Primitive::Float64,
+                BitCast::new(
+                    types::Primitive::Integer64,
+                    types::Primitive::Float64,
+                    Variable::new("x"),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn remove_identity_bit_cast() {
+        assert_eq!(
+            transform_module(create_module(
+                types::Primitive::Integer64,
+                BitCast::new(
+                    types::Primitive::Integer64,
+                    types::Primitive::Integer64,
+                    Variable::new("x"),
+                ),
+            )),
+            create_module(types::Primitive::Integer64, Variable::new("x"))
+        );
+    }
+
+    #[test]
+    fn remove_round_trip_bit_casts() {
+        assert_eq!(
+            transform_module(create_module(
+                types::Primitive::Integer64,
+                BitCast::new(
+                    types::Primitive::Float64,
+                    types::Primitive::Integer64,
+                    BitCast::new(
+                        types::Primitive::Integer64,
+                        types::Primitive::Float64,
+                        Variable::new("x"),
+                    ),
+                ),
+            )),
+            create_module(types::Primitive::Integer64, Variable::new("x"))
+        );
+    }
+}
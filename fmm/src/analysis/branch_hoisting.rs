@@ -0,0 +1,176 @@
+use super::rename;
+use crate::ir::*;
+use std::mem::take;
+
+// Hoist identical instructions at the beginning of both branches of if
+// instructions. They are executed on either branch before anything else and
+// so even ones with side effects can be hoisted.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_block(definition.body_mut());
+    }
+}
+
+fn transform_block(block: &mut Block) {
+    let mut instructions = Vec::with_capacity(block.instructions().len());
+
+    for mut instruction in take(block.instructions_mut()) {
+        if let Instruction::If(if_) = &mut instruction {
+            transform_block(if_.then_mut());
+            transform_block(if_.else_mut());
+
+            while let Some(instruction) = hoist(if_) {
+                instructions.push(instruction);
+            }
+        }
+
+        instructions.push(instruction);
+    }
+
+    *block.instructions_mut() = instructions;
+}
+
+fn hoist(if_: &mut If) -> Option<Instruction> {
+    let then = if_.then().instructions().first()?;
+    let else_ = if_.else_().instructions().first()?;
+
+    if then.has_blocks() {
+        return None;
+    }
+
+    let names = match (then.value(), else_.value()) {
+        (Some((then_name, _)), Some((else_name, _))) => {
+            Some((then_name.to_owned(), else_name.to_owned()))
+        }
+        (None, None) => None,
+        _ => return None,
+    };
+    let rename = |name: &str| match &names {
+        Some((then_name, else_name)) if name == else_name => then_name.clone(),
+        _ => name.into(),
+    };
+
+    if then != &rename::rename_instruction(else_, &rename) {
+        return None;
+    }
+
+    let instruction = if_.then_mut().instructions_mut().remove(0);
+    if_.else_mut().instructions_mut().remove(0);
+
+    if names.is_some() {
+        for instruction in if_.else_mut().instructions_mut() {
+            *instruction = rename::rename_instruction(instruction, &rename);
+        }
+
+        *if_.else_mut().terminal_instruction_mut() =
+            rename::rename_terminal_instruction(if_.else_().terminal_instruction(), &rename);
+    }
+
+    Some(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::type_check, types};
+    use pretty_assertions::assert_eq;
+
+    fn record_type() -> types::Record {
+        types::Record::new(vec![
+            types::Primitive::PointerInteger.into(),
+            types::Primitive::PointerInteger.into(),
+        ])
+    }
+
+    fn create_load(name: &str) -> Instruction {
+        Load::new(
+            types::Primitive::PointerInteger,
+            RecordAddress::new(record_type(), Variable::new("p"), 1),
+            name,
+        )
+        .into()
+    }
+
+    fn create_module(instructions: Vec<Instruction>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![
+                    Argument::new("p", types::Pointer::new(record_type())),
+                    Argument::new("c", types::Primitive::Boolean),
+                ],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    instructions,
+                    Return::new(types::Primitive::PointerInteger, Variable::new("z")),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn create_if(
+        then: Vec<Instruction>,
+        then_name: &str,
+        else_: Vec<Instruction>,
+        else_name: &str,
+    ) -> Instruction {
+        If::new(
+            types::Primitive::PointerInteger,
+            Variable::new("c"),
+            Block::new(
+                then,
+                Branch::new(types::Primitive::PointerInteger, Variable::new(then_name)),
+            ),
+            Block::new(
+                else_,
+                Branch::new(types::Primitive::PointerInteger, Variable::new(else_name)),
+            ),
+            "z",
+        )
+        .into()
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn hoist_common_load() {
+        assert_eq!(
+            transform_module(create_module(vec![create_if(
+                vec![create_load("x")],
+                "x",
+                vec![create_load("y")],
+                "y"
+            )])),
+            create_module(vec![create_load("x"), create_if(vec![], "x", vec![], "x")])
+        );
+    }
+
+    #[test]
+    fn keep_different_instructions() {
+        let module = create_module(vec![create_if(
+            vec![create_load("x")],
+            "x",
+            vec![Load::new(
+                types::Primitive::PointerInteger,
+                RecordAddress::new(record_type(), Variable::new("p"), 0),
+                "y",
+            )
+            .into()],
+            "y",
+        )]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -2,24 +2,56 @@ mod call;
 mod context;
 mod error;
 mod function_definition;
+mod trace;
 mod type_;
 
+pub use self::trace::TransformTrace;
 use self::{context::Context, error::CCallingConventionError};
 use super::type_conversion;
-use crate::{ir::*, types::Type};
+use crate::{
+    ir::*,
+    types::{self, Type},
+};
+use fnv::FnvHashMap;
 
 // TODO Implement the complete C calling convention for all targets.
 //
 // Based on: https://refspecs.linuxfoundation.org/elf/x86_64-SysV-psABI.pdf
 pub fn transform(module: &mut Module, word_bytes: usize) -> Result<(), CCallingConventionError> {
+    transform_module(module, word_bytes, None)
+}
+
+// Traces keyed by function names correlate original instructions with
+// transformed ones for debugging.
+pub fn transform_with_traces(
+    module: &mut Module,
+    word_bytes: usize,
+) -> Result<FnvHashMap<String, TransformTrace>, CCallingConventionError> {
+    let mut traces = FnvHashMap::default();
+
+    transform_module(module, word_bytes, Some(&mut traces))?;
+
+    Ok(traces)
+}
+
+fn transform_module(
+    module: &mut Module,
+    word_bytes: usize,
+    mut traces: Option<&mut FnvHashMap<String, TransformTrace>>,
+) -> Result<(), CCallingConventionError> {
     if ![4, 8].contains(&word_bytes) {
         return Err(CCallingConventionError::WordSize(word_bytes));
     }
 
     let context = Context::new(word_bytes);
     for definition in module.function_definitions_mut() {
-        function_definition::transform(&context, definition);
-        call::transform_function_definition(&context, definition)?;
+        let count = function_definition::transform(&context, definition);
+        let mut trace = call::transform_function_definition(&context, definition)?;
+
+        if let Some(traces) = &mut traces {
+            trace.skip(count);
+            traces.insert(definition.name().into(), trace);
+        }
     }
 
     type_conversion::convert(module, &|type_| match type_ {
@@ -30,6 +62,53 @@ pub fn transform(module: &mut Module, word_bytes: usize) -> Result<(), CCallingC
     Ok(())
 }
 
+pub fn is_abi_compatible(
+    one: &types::Function,
+    other: &types::Function,
+    word_bytes: usize,
+) -> bool {
+    type_::is_abi_compatible(&Context::new(word_bytes), one, other)
+}
+
+// Aggregates of the memory class are passed via pointers to stack frames of
+// callers and so forbid guaranteed tail calls.
+pub fn is_tail_call_compatible(
+    caller: &types::Function,
+    callee: &types::Function,
+    word_bytes: usize,
+) -> bool {
+    let context = Context::new(word_bytes);
+
+    type_::is_abi_compatible(&context, caller, callee)
+        && !callee
+            .arguments()
+            .iter()
+            .chain([callee.result()])
+            .any(|type_| type_::is_memory_class(&context, type_))
+}
+
+// Arities are counted after the transformation including struct return
+// arguments.
+pub fn max_call_arity(definition: &FunctionDefinition, word_bytes: usize) -> usize {
+    max_call_arity_in_block(&Context::new(word_bytes), definition.body())
+}
+
+fn max_call_arity_in_block(context: &Context, block: &Block) -> usize {
+    block
+        .instructions()
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::Call(call) => type_::transform_function(context, call.type_())
+                .arguments()
+                .len(),
+            Instruction::If(if_) => max_call_arity_in_block(context, if_.then())
+                .max(max_call_arity_in_block(context, if_.else_())),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,6 +309,34 @@ mod tests {
             assert_eq!(transform_module(module.clone()), Ok(module));
         }
 
+        #[test]
+        fn keep_load_hints() {
+            let pointer_type = types::Pointer::new(types::Primitive::Integer64);
+            let module = Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![FunctionDefinition::new(
+                    "g",
+                    vec![Argument::new("x", pointer_type)],
+                    types::Primitive::Integer64,
+                    Block::new(
+                        vec![
+                            Load::new(types::Primitive::Integer64, Variable::new("x"), "y")
+                                .set_alignment(Some(16))
+                                .set_dereferenceable_bytes(Some(64))
+                                .into(),
+                        ],
+                        Return::new(types::Primitive::Integer64, Variable::new("y")),
+                    ),
+                    FunctionDefinitionOptions::new()
+                        .set_calling_convention(types::CallingConvention::Target),
+                )],
+            );
+
+            assert_eq!(transform_module(module.clone()), Ok(module));
+        }
+
         #[test]
         fn transform_function_definition() {
             let record_type = types::Record::new(vec![
@@ -276,7 +383,8 @@ mod tests {
                             Return::new(void_type(), void_value()),
                         ),
                         FunctionDefinitionOptions::new()
-                            .set_calling_convention(types::CallingConvention::Target),
+                            .set_calling_convention(types::CallingConvention::Target)
+                            .set_struct_return_argument(true),
                     )],
                 ))
             );
@@ -364,7 +472,8 @@ mod tests {
                                 Return::new(void_type(), void_value()),
                             ),
                             FunctionDefinitionOptions::new()
-                                .set_calling_convention(types::CallingConvention::Target),
+                                .set_calling_convention(types::CallingConvention::Target)
+                                .set_struct_return_argument(true),
                         ),
                         FunctionDefinition::new(
                             "g",
@@ -803,4 +912,277 @@ mod tests {
             );
         }
     }
+
+    mod free_heap {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn create_module(type_: impl Into<types::Type>) -> Module {
+            Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![FunctionDefinition::new(
+                    "f",
+                    vec![Argument::new("x", types::generic_pointer_type())],
+                    void_type(),
+                    Block::new(
+                        vec![FreeHeap::new_sized(type_, Variable::new("x")).into()],
+                        Return::new(void_type(), void_value()),
+                    ),
+                    Default::default(),
+                )],
+            )
+        }
+
+        #[test]
+        fn transform_sized_free_of_record() {
+            let module = create_module(types::Record::new(vec![
+                types::Primitive::Integer64.into(),
+                types::Primitive::Integer64.into(),
+                types::Primitive::Integer64.into(),
+            ]));
+
+            assert_eq!(transform_module(module.clone()), Ok(module));
+        }
+
+        #[test]
+        fn transform_sized_free_of_record_with_function() {
+            let record_type = types::Record::new(vec![
+                types::Primitive::Integer64.into(),
+                types::Primitive::Integer64.into(),
+                types::Primitive::Integer64.into(),
+            ]);
+
+            assert_eq!(
+                transform_module(create_module(types::Record::new(vec![
+                    types::Function::new(
+                        vec![],
+                        record_type.clone(),
+                        types::CallingConvention::Target
+                    )
+                    .into()
+                ]))),
+                Ok(create_module(types::Record::new(vec![
+                    types::Function::new(
+                        vec![types::Pointer::new(record_type).into()],
+                        void_type(),
+                        types::CallingConvention::Target
+                    )
+                    .into()
+                ])))
+            );
+        }
+    }
+
+    #[test]
+    fn calculate_max_call_arity() {
+        let arguments = vec![types::Primitive::Integer64.into(); 3];
+        let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 3]);
+        let create_call = |result_type: Type, name| -> Instruction {
+            Call::new(
+                types::Function::new(
+                    arguments.clone(),
+                    result_type,
+                    types::CallingConvention::Target,
+                ),
+                Variable::new("g"),
+                vec![Primitive::Integer64(42).into(); 3],
+                name,
+            )
+            .into()
+        };
+        let create_definition = |instructions| {
+            FunctionDefinition::new(
+                "f",
+                vec![],
+                void_type(),
+                Block::new(instructions, Return::new(void_type(), void_value())),
+                Default::default(),
+            )
+        };
+
+        assert_eq!(max_call_arity(&create_definition(vec![]), WORD_BYTES), 0);
+        assert_eq!(
+            max_call_arity(
+                &create_definition(vec![create_call(types::Primitive::Integer64.into(), "x")]),
+                WORD_BYTES
+            ),
+            3
+        );
+        assert_eq!(
+            max_call_arity(
+                &create_definition(vec![
+                    create_call(types::Primitive::Integer64.into(), "x"),
+                    If::new(
+                        void_type(),
+                        Primitive::Boolean(true),
+                        Block::new(
+                            vec![create_call(record_type.into(), "y")],
+                            Branch::new(void_type(), void_value())
+                        ),
+                        Block::new(vec![], Branch::new(void_type(), void_value())),
+                        "z",
+                    )
+                    .into()
+                ]),
+                WORD_BYTES
+            ),
+            4
+        );
+    }
+
+    mod trace {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn create_function_type(record_type: &types::Record) -> types::Function {
+            types::Function::new(
+                vec![record_type.clone().into()],
+                record_type.clone(),
+                types::CallingConvention::Target,
+            )
+        }
+
+        fn create_spilled_call(record_type: &types::Record, name: &str) -> Instruction {
+            Call::new(
+                create_function_type(record_type),
+                Variable::new("g"),
+                vec![Undefined::new(record_type.clone()).into()],
+                name,
+            )
+            .into()
+        }
+
+        fn transform_definition(
+            record_type: &types::Record,
+            definition: FunctionDefinition,
+        ) -> (FunctionDefinition, TransformTrace) {
+            let mut module = Module::new(
+                vec![],
+                vec![FunctionDeclaration::new(
+                    "g",
+                    create_function_type(record_type),
+                )],
+                vec![],
+                vec![definition],
+            );
+
+            validation::validate(&module).unwrap();
+
+            let traces = transform_with_traces(&mut module, WORD_BYTES).unwrap();
+
+            validation::validate(&module).unwrap();
+
+            (
+                module.function_definitions()[0].clone(),
+                traces["f"].clone(),
+            )
+        }
+
+        #[test]
+        fn trace_spilled_call() {
+            let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 3]);
+
+            let (definition, trace) = transform_definition(
+                &record_type,
+                FunctionDefinition::new(
+                    "f",
+                    vec![Argument::new(
+                        "p",
+                        types::Pointer::new(types::Primitive::Integer64),
+                    )],
+                    void_type(),
+                    Block::new(
+                        vec![
+                            Load::new(types::Primitive::Integer64, Variable::new("p"), "x").into(),
+                            create_spilled_call(&record_type, "y"),
+                        ],
+                        Return::new(void_type(), void_value()),
+                    ),
+                    Default::default(),
+                ),
+            );
+
+            assert_eq!(trace.len(), 2);
+            assert_eq!(trace.expansion(0), Some(0..1));
+            assert_eq!(trace.expansion(1), Some(1..6));
+            assert!(matches!(
+                &definition.body().instructions()[1..6],
+                [
+                    Instruction::AllocateStack(_),
+                    Instruction::Store(_),
+                    Instruction::AllocateStack(_),
+                    Instruction::Call(_),
+                    Instruction::Load(_),
+                ]
+            ));
+        }
+
+        #[test]
+        fn skip_argument_loads() {
+            let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 3]);
+
+            let (definition, trace) = transform_definition(
+                &record_type,
+                FunctionDefinition::new(
+                    "f",
+                    vec![Argument::new("x", record_type.clone())],
+                    void_type(),
+                    Block::new(
+                        vec![create_spilled_call(&record_type, "y")],
+                        Return::new(void_type(), void_value()),
+                    ),
+                    FunctionDefinitionOptions::new()
+                        .set_calling_convention(types::CallingConvention::Target),
+                ),
+            );
+
+            assert_eq!(trace.len(), 1);
+            assert_eq!(trace.expansion(0), Some(1..6));
+            assert!(matches!(
+                definition.body().instructions()[0],
+                Instruction::Load(_)
+            ));
+        }
+
+        #[test]
+        fn trace_call_in_if() {
+            let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 3]);
+
+            let (definition, trace) = transform_definition(
+                &record_type,
+                FunctionDefinition::new(
+                    "f",
+                    vec![],
+                    void_type(),
+                    Block::new(
+                        vec![If::new(
+                            void_type(),
+                            Primitive::Boolean(true),
+                            Block::new(
+                                vec![create_spilled_call(&record_type, "y")],
+                                Branch::new(void_type(), void_value()),
+                            ),
+                            Block::new(vec![], Branch::new(void_type(), void_value())),
+                            "z",
+                        )
+                        .into()],
+                        Return::new(void_type(), void_value()),
+                    ),
+                    Default::default(),
+                ),
+            );
+
+            assert_eq!(trace.expansion(0), Some(0..1));
+            assert_eq!(trace.then(0).unwrap().expansion(0), Some(0..5));
+            assert!(trace.else_(0).unwrap().is_empty());
+
+            if let Instruction::If(if_) = &definition.body().instructions()[0] {
+                assert_eq!(if_.then().instructions().len(), 5);
+            } else {
+                unreachable!();
+            }
+        }
+    }
 }
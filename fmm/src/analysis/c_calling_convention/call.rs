@@ -1,4 +1,4 @@
-use super::{context::Context, error::CCallingConventionError, type_};
+use super::{context::Context, error::CCallingConventionError, trace::TransformTrace, type_};
 use crate::{
     build::{InstructionBuilder, TypedExpression},
     ir::*,
@@ -8,29 +8,35 @@ use crate::{
 pub fn transform_function_definition(
     context: &Context,
     definition: &mut FunctionDefinition,
-) -> Result<(), CCallingConventionError> {
-    transform_block(context, definition.body_mut())?;
-
-    Ok(())
+) -> Result<TransformTrace, CCallingConventionError> {
+    transform_block(context, definition.body_mut())
 }
 
-fn transform_block(context: &Context, block: &mut Block) -> Result<(), CCallingConventionError> {
+fn transform_block(
+    context: &Context,
+    block: &mut Block,
+) -> Result<TransformTrace, CCallingConventionError> {
     let mut instructions = Vec::with_capacity(block.instructions().len());
+    let mut trace = TransformTrace::new();
 
     for instruction in block.instructions_mut().drain(..) {
-        transform_instruction(context, instruction, &mut instructions)?;
+        let start = instructions.len();
+        let branches = transform_instruction(context, instruction, &mut instructions)?;
+
+        trace.push(start..instructions.len(), branches);
     }
 
     *block.instructions_mut() = instructions;
 
-    Ok(())
+    Ok(trace)
 }
 
+// Traces of branches are returned for if instructions.
 fn transform_instruction(
     context: &Context,
     instruction: Instruction,
     instructions: &mut Vec<Instruction>,
-) -> Result<(), CCallingConventionError> {
+) -> Result<Option<(TransformTrace, TransformTrace)>, CCallingConventionError> {
     match instruction {
         Instruction::Call(call)
             if call.type_().calling_convention() == types::CallingConvention::Target =>
@@ -47,6 +53,10 @@ fn transform_instruction(
                 .iter()
                 .zip(original_function_type.arguments())
             {
+                if type_::is_zero_sized(context, type_) {
+                    continue;
+                }
+
                 let argument = TypedExpression::new(argument.clone(), type_.clone());
 
                 if type_::is_memory_class(context, type_) {
@@ -63,10 +73,7 @@ fn transform_instruction(
             if type_::is_memory_class(context, original_function_type.result()) {
                 let pointer = builder.allocate_stack(original_function_type.result().clone());
 
-                builder.call(
-                    function,
-                    [pointer.clone()].into_iter().chain(arguments).collect(),
-                )?;
+                builder.call(function, [pointer.clone()].into_iter().chain(arguments))?;
 
                 builder.add_instruction(Load::new(
                     original_function_type.result().clone(),
@@ -74,29 +81,34 @@ fn transform_instruction(
                     call.name(),
                 ));
             } else {
-                builder.add_instruction(Call::new(
-                    function_type,
-                    function.expression().clone(),
-                    arguments
-                        .into_iter()
-                        .map(|argument| argument.expression().clone())
-                        .collect(),
-                    call.name(),
-                ));
+                builder.add_instruction(
+                    Call::new(
+                        function_type,
+                        function.expression().clone(),
+                        arguments
+                            .into_iter()
+                            .map(|argument| argument.expression().clone())
+                            .collect(),
+                        call.name(),
+                    )
+                    .set_tail_call(call.tail_call()),
+                );
             }
 
             instructions.extend(builder.into_instructions());
         }
         Instruction::If(mut if_) => {
-            transform_block(context, if_.then_mut())?;
-            transform_block(context, if_.else_mut())?;
+            let then = transform_block(context, if_.then_mut())?;
+            let else_ = transform_block(context, if_.else_mut())?;
 
             instructions.push(if_.into());
+
+            return Ok(Some((then, else_)));
         }
         _ => instructions.push(instruction),
     }
 
-    Ok(())
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -454,4 +466,149 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn transform_likely_if() {
+        let record_type = types::Record::new(vec![
+            types::Primitive::Integer64.into(),
+            types::Primitive::Integer64.into(),
+            types::Primitive::Integer64.into(),
+        ]);
+
+        let definition = transform(FunctionDefinition::new(
+            "f",
+            vec![],
+            void_type(),
+            Block::new(
+                vec![If::new(
+                    void_type(),
+                    Primitive::Boolean(true),
+                    Block::new(
+                        vec![Call::new(
+                            types::Function::new(
+                                vec![record_type.clone().into()],
+                                void_type(),
+                                types::CallingConvention::Target,
+                            ),
+                            Variable::new("g"),
+                            vec![Undefined::new(record_type).into()],
+                            "x",
+                        )
+                        .into()],
+                        Branch::new(void_type(), void_value()),
+                    ),
+                    Block::new(vec![], Branch::new(void_type(), void_value())),
+                    "y",
+                )
+                .set_likely(Some(IfBranch::Then))
+                .into()],
+                Return::new(void_type(), void_value()),
+            ),
+            FunctionDefinitionOptions::new()
+                .set_calling_convention(types::CallingConvention::Target),
+        ))
+        .unwrap();
+
+        if let Instruction::If(if_) = &definition.body().instructions()[0] {
+            assert_eq!(if_.then().instructions().len(), 3);
+            assert_eq!(if_.likely(), Some(IfBranch::Then));
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn generate_unique_spill_names_for_calls_of_same_names() {
+        let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 3]);
+        let call = Call::new(
+            types::Function::new(
+                vec![record_type.clone().into()],
+                types::Primitive::Integer64,
+                types::CallingConvention::Target,
+            ),
+            Variable::new("g"),
+            vec![Undefined::new(record_type).into()],
+            "x",
+        );
+
+        let definition = transform(FunctionDefinition::new(
+            "f",
+            vec![],
+            types::Primitive::Integer64,
+            Block::new(
+                vec![call.clone().into(), call.into()],
+                Return::new(types::Primitive::Integer64, Variable::new("x")),
+            ),
+            FunctionDefinitionOptions::new()
+                .set_calling_convention(types::CallingConvention::Target),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            definition
+                .body()
+                .instructions()
+                .iter()
+                .filter_map(|instruction| match instruction {
+                    Instruction::AllocateStack(allocate) => Some(allocate.name()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            vec!["_c_0", "_c_1"]
+        );
+    }
+
+    #[test]
+    fn drop_zero_sized_argument() {
+        let function_type = |arguments| {
+            types::Function::new(
+                arguments,
+                types::Primitive::Integer64,
+                types::CallingConvention::Target,
+            )
+        };
+
+        assert_eq!(
+            transform(FunctionDefinition::new(
+                "f",
+                vec![],
+                types::Primitive::Integer64,
+                Block::new(
+                    vec![Call::new(
+                        function_type(vec![
+                            types::Primitive::Integer64.into(),
+                            types::Record::new(vec![]).into()
+                        ]),
+                        Variable::new("g"),
+                        vec![
+                            Primitive::Integer64(42).into(),
+                            Record::new(types::Record::new(vec![]), vec![]).into()
+                        ],
+                        "x",
+                    )
+                    .into()],
+                    Return::new(types::Primitive::Integer64, Variable::new("x")),
+                ),
+                FunctionDefinitionOptions::new()
+                    .set_calling_convention(types::CallingConvention::Target),
+            )),
+            Ok(FunctionDefinition::new(
+                "f",
+                vec![],
+                types::Primitive::Integer64,
+                Block::new(
+                    vec![Call::new(
+                        function_type(vec![types::Primitive::Integer64.into()]),
+                        Variable::new("g"),
+                        vec![Primitive::Integer64(42).into()],
+                        "x",
+                    )
+                    .into()],
+                    Return::new(types::Primitive::Integer64, Variable::new("x")),
+                ),
+                FunctionDefinitionOptions::new()
+                    .set_calling_convention(types::CallingConvention::Target),
+            ))
+        );
+    }
 }
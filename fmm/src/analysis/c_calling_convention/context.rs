@@ -1,9 +1,10 @@
-use crate::build::NameGenerator;
+use crate::{analysis::type_size::LayoutCache, build::NameGenerator};
 use std::{cell::RefCell, rc::Rc};
 
 pub struct Context {
     name_generator: Rc<RefCell<NameGenerator>>,
     word_bytes: usize,
+    layout_cache: LayoutCache,
 }
 
 impl Context {
@@ -11,6 +12,7 @@ impl Context {
         Self {
             name_generator: Rc::new(NameGenerator::new("_c_").into()),
             word_bytes,
+            layout_cache: LayoutCache::new(),
         }
     }
 
@@ -21,4 +23,8 @@ impl Context {
     pub fn word_bytes(&self) -> usize {
         self.word_bytes
     }
+
+    pub fn layout_cache(&self) -> &LayoutCache {
+        &self.layout_cache
+    }
 }
@@ -1,12 +1,15 @@
 use super::{context::Context, type_};
 use crate::{
+    analysis::expression_conversion,
     ir::*,
     types::{self, void_type, Type},
 };
 
-pub fn transform(context: &Context, definition: &mut FunctionDefinition) {
+// Returns a number of instructions loading arguments inserted at the beginning
+// of a function body.
+pub fn transform(context: &Context, definition: &mut FunctionDefinition) -> usize {
     if definition.type_().calling_convention() != types::CallingConvention::Target {
-        return;
+        return 0;
     }
 
     let result_pointer = if type_::is_memory_class(context, definition.result_type()) {
@@ -25,11 +28,19 @@ pub fn transform(context: &Context, definition: &mut FunctionDefinition) {
         arguments.push(Argument::new(name, type_::transform_memory_class(type_)));
     }
 
+    let mut zero_sized_arguments = vec![];
+
     for argument in definition.arguments_mut().drain(..) {
-        if type_::is_memory_class(context, argument.type_()) {
-            arguments.push(Argument::new(
+        if type_::is_zero_sized(context, argument.type_()) {
+            zero_sized_arguments.push(argument);
+        } else if type_::is_memory_class(context, argument.type_()) {
+            arguments.push(Argument::with_options(
                 pointer_name(argument.name()),
                 type_::transform_memory_class(argument.type_()),
+                argument
+                    .options()
+                    .clone()
+                    .set_original_type(Some(argument.type_().clone())),
             ));
             instructions.push(
                 Load::new(
@@ -47,10 +58,43 @@ pub fn transform(context: &Context, definition: &mut FunctionDefinition) {
     *definition.arguments_mut() = arguments;
 
     if result_pointer.is_some() {
-        *definition.result_type_mut() = void_type().into()
+        *definition.result_type_mut() = void_type().into();
+        *definition.options_mut() = definition
+            .options()
+            .clone()
+            .set_struct_return_argument(true);
+    }
+
+    if !zero_sized_arguments.is_empty() {
+        replace_zero_sized_arguments(definition.body_mut(), &zero_sized_arguments);
     }
 
+    let count = instructions.len();
+
     transform_block(definition.body_mut(), instructions, result_pointer.as_ref());
+
+    count
+}
+
+// Zero-sized values have no bits and so they are replaced with undefined ones.
+fn replace_zero_sized_arguments(block: &mut Block, arguments: &[Argument]) {
+    let convert = |expression: &Expression| match expression {
+        Expression::Variable(variable) => arguments
+            .iter()
+            .find(|argument| argument.name() == variable.name())
+            .map(|argument| Undefined::new(argument.type_().clone()).into())
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    };
+
+    for instruction in block.instructions_mut() {
+        expression_conversion::convert_in_instruction(instruction, &convert);
+    }
+
+    expression_conversion::convert_in_terminal_instruction(
+        block.terminal_instruction_mut(),
+        &convert,
+    );
 }
 
 fn transform_block(
@@ -138,9 +182,10 @@ mod tests {
             )),
             FunctionDefinition::new(
                 "f",
-                vec![Argument::new(
+                vec![Argument::with_options(
                     "x_p",
-                    types::Pointer::new(record_type.clone())
+                    types::Pointer::new(record_type.clone()),
+                    ArgumentOptions::new().set_original_type(Some(record_type.clone().into())),
                 )],
                 void_type(),
                 Block::new(
@@ -190,7 +235,8 @@ mod tests {
                     Return::new(void_type(), void_value()),
                 ),
                 FunctionDefinitionOptions::new()
-                    .set_calling_convention(types::CallingConvention::Target),
+                    .set_calling_convention(types::CallingConvention::Target)
+                    .set_struct_return_argument(true),
             )
         );
     }
@@ -232,7 +278,8 @@ mod tests {
                     Return::new(void_type(), void_value()),
                 ),
                 FunctionDefinitionOptions::new()
-                    .set_calling_convention(types::CallingConvention::Target),
+                    .set_calling_convention(types::CallingConvention::Target)
+                    .set_struct_return_argument(true),
             )
         );
     }
@@ -304,6 +351,103 @@ mod tests {
                     .into(),],
                     TerminalInstruction::Unreachable
                 ),
+                FunctionDefinitionOptions::new()
+                    .set_calling_convention(types::CallingConvention::Target)
+                    .set_struct_return_argument(true),
+            )
+        );
+    }
+
+    #[test]
+    fn mark_struct_return_argument() {
+        let record_type = types::Record::new(vec![
+            types::Primitive::Integer64.into(),
+            types::Primitive::Integer64.into(),
+            types::Primitive::Integer64.into(),
+        ]);
+
+        let definition = transform_definition(FunctionDefinition::new(
+            "f",
+            vec![Argument::new("x", types::Primitive::PointerInteger)],
+            record_type.clone(),
+            Block::new(
+                vec![],
+                Return::new(record_type.clone(), Undefined::new(record_type.clone())),
+            ),
+            FunctionDefinitionOptions::new()
+                .set_calling_convention(types::CallingConvention::Target),
+        ));
+
+        assert!(definition.options().has_struct_return_argument());
+        assert_eq!(
+            definition.struct_return_argument(),
+            Some(&Argument::new("f_p", types::Pointer::new(record_type)))
+        );
+    }
+
+    #[test]
+    fn do_not_mark_struct_return_argument() {
+        let definition = transform_definition(FunctionDefinition::new(
+            "f",
+            vec![Argument::new("x", types::Primitive::PointerInteger)],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![],
+                Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+            ),
+            FunctionDefinitionOptions::new()
+                .set_calling_convention(types::CallingConvention::Target),
+        ));
+
+        assert!(!definition.options().has_struct_return_argument());
+        assert_eq!(definition.struct_return_argument(), None);
+    }
+
+    #[test]
+    fn keep_alignment() {
+        let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 4]);
+
+        let definition = transform_definition(FunctionDefinition::new(
+            "f",
+            vec![Argument::new("x", record_type.clone())],
+            record_type.clone(),
+            Block::new(vec![], Return::new(record_type, Variable::new("x"))),
+            FunctionDefinitionOptions::new()
+                .set_alignment(Some(64))
+                .set_calling_convention(types::CallingConvention::Target),
+        ));
+
+        assert_eq!(definition.options().alignment(), Some(64));
+    }
+
+    #[test]
+    fn drop_zero_sized_argument() {
+        assert_eq!(
+            transform_definition(FunctionDefinition::new(
+                "f",
+                vec![
+                    Argument::new("x", types::Primitive::Integer64),
+                    Argument::new("y", types::Record::new(vec![])),
+                ],
+                types::Record::new(vec![]),
+                Block::new(
+                    vec![],
+                    Return::new(types::Record::new(vec![]), Variable::new("y"))
+                ),
+                FunctionDefinitionOptions::new()
+                    .set_calling_convention(types::CallingConvention::Target),
+            )),
+            FunctionDefinition::new(
+                "f",
+                vec![Argument::new("x", types::Primitive::Integer64)],
+                types::Record::new(vec![]),
+                Block::new(
+                    vec![],
+                    Return::new(
+                        types::Record::new(vec![]),
+                        Undefined::new(types::Record::new(vec![]))
+                    )
+                ),
                 FunctionDefinitionOptions::new()
                     .set_calling_convention(types::CallingConvention::Target),
             )
@@ -0,0 +1,66 @@
+use std::ops::Range;
+
+// Ranges of transformed instructions keyed by indices of original ones in
+// blocks. Traces of branches are kept for if instructions.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransformTrace {
+    expansions: Vec<Expansion>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Expansion {
+    range: Range<usize>,
+    branches: Option<Box<(TransformTrace, TransformTrace)>>,
+}
+
+impl TransformTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn expansion(&self, index: usize) -> Option<Range<usize>> {
+        self.expansions
+            .get(index)
+            .map(|expansion| expansion.range.clone())
+    }
+
+    pub fn then(&self, index: usize) -> Option<&TransformTrace> {
+        self.branches(index).map(|(then, _)| then)
+    }
+
+    pub fn else_(&self, index: usize) -> Option<&TransformTrace> {
+        self.branches(index).map(|(_, else_)| else_)
+    }
+
+    pub fn len(&self) -> usize {
+        self.expansions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expansions.is_empty()
+    }
+
+    pub(super) fn push(
+        &mut self,
+        range: Range<usize>,
+        branches: Option<(TransformTrace, TransformTrace)>,
+    ) {
+        self.expansions.push(Expansion {
+            range,
+            branches: branches.map(Box::new),
+        });
+    }
+
+    // Instructions inserted by transformation are not original ones.
+    pub(super) fn skip(&mut self, count: usize) {
+        self.expansions.drain(..count.min(self.expansions.len()));
+    }
+
+    fn branches(&self, index: usize) -> Option<(&TransformTrace, &TransformTrace)> {
+        self.expansions
+            .get(index)?
+            .branches
+            .as_deref()
+            .map(|(then, else_)| (then, else_))
+    }
+}
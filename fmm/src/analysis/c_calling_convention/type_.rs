@@ -1,8 +1,5 @@
 use super::context::Context;
-use crate::{
-    analysis::type_size,
-    types::{self, void_type, Type},
-};
+use crate::types::{self, void_type, Type};
 
 pub fn transform_memory_class(type_: &Type) -> Type {
     types::Pointer::new(type_.clone()).into()
@@ -15,8 +12,12 @@ pub fn transform_function(context: &Context, function: &types::Function) -> type
             .arguments()
             .iter()
             .any(|type_| is_memory_class(context, type_));
+        let is_argument_zero_sized = function
+            .arguments()
+            .iter()
+            .any(|type_| is_zero_sized(context, type_));
 
-        if is_result_memory || is_argument_memory {
+        if is_result_memory || is_argument_memory || is_argument_zero_sized {
             types::Function::new(
                 if is_result_memory {
                     Some(transform_memory_class(function.result()))
@@ -24,13 +25,19 @@ pub fn transform_function(context: &Context, function: &types::Function) -> type
                     None
                 }
                 .into_iter()
-                .chain(function.arguments().iter().map(|type_| {
-                    if is_memory_class(context, type_) {
-                        transform_memory_class(type_)
-                    } else {
-                        type_.clone()
-                    }
-                }))
+                .chain(
+                    function
+                        .arguments()
+                        .iter()
+                        .filter(|type_| !is_zero_sized(context, type_))
+                        .map(|type_| {
+                            if is_memory_class(context, type_) {
+                                transform_memory_class(type_)
+                            } else {
+                                type_.clone()
+                            }
+                        }),
+                )
                 .collect(),
                 if is_result_memory {
                     void_type().into()
@@ -47,11 +54,37 @@ pub fn transform_function(context: &Context, function: &types::Function) -> type
     }
 }
 
+// Memory class aggregates are passed by pointer regardless of their sizes.
+pub fn is_abi_compatible(
+    context: &Context,
+    one: &types::Function,
+    other: &types::Function,
+) -> bool {
+    let is_type_compatible = |one: &Type, other: &Type| {
+        one == other || is_memory_class(context, one) && is_memory_class(context, other)
+    };
+
+    one.calling_convention() == other.calling_convention()
+        && one.arguments().len() == other.arguments().len()
+        && one
+            .arguments()
+            .iter()
+            .zip(other.arguments())
+            .all(|(one, other)| is_type_compatible(one, other))
+        && is_type_compatible(one.result(), other.result())
+}
+
+// Zero-sized arguments are not passed at all as in C.
+pub fn is_zero_sized(context: &Context, type_: &Type) -> bool {
+    matches!(type_, Type::Record(_) | Type::Union(_))
+        && context.layout_cache().size(type_, context.word_bytes()) == 0
+}
+
 // The name, "memory class" comes from the C ABI on System V.
 pub fn is_memory_class(context: &Context, type_: &Type) -> bool {
     match type_ {
         Type::Record(record) => {
-            type_size::calculate_size(type_, context.word_bytes()) > 2 * context.word_bytes()
+            context.layout_cache().size(type_, context.word_bytes()) > 2 * context.word_bytes()
                 || record
                     .fields()
                     .iter()
@@ -179,6 +212,81 @@ mod tests {
         }
     }
 
+    mod abi_compatibility {
+        use super::*;
+
+        fn create_record(size: usize) -> types::Record {
+            types::Record::new(vec![types::Primitive::Integer64.into(); size])
+        }
+
+        #[test]
+        fn check_same_functions() {
+            let function = types::Function::new(
+                vec![types::Primitive::PointerInteger.into()],
+                types::Primitive::Float64,
+                types::CallingConvention::Target,
+            );
+
+            assert!(is_abi_compatible(
+                &Context::new(WORD_BYTES),
+                &function,
+                &function
+            ));
+        }
+
+        #[test]
+        fn check_functions_with_different_memory_class_arguments() {
+            assert!(is_abi_compatible(
+                &Context::new(WORD_BYTES),
+                &types::Function::new(
+                    vec![create_record(3).into()],
+                    void_type(),
+                    types::CallingConvention::Target,
+                ),
+                &types::Function::new(
+                    vec![create_record(4).into()],
+                    void_type(),
+                    types::CallingConvention::Target,
+                )
+            ));
+        }
+
+        #[test]
+        fn check_functions_with_different_memory_class_results() {
+            assert!(is_abi_compatible(
+                &Context::new(WORD_BYTES),
+                &types::Function::new(vec![], create_record(3), types::CallingConvention::Target),
+                &types::Function::new(vec![], create_record(4), types::CallingConvention::Target)
+            ));
+        }
+
+        #[test]
+        fn check_functions_with_different_register_class_arguments() {
+            assert!(!is_abi_compatible(
+                &Context::new(WORD_BYTES),
+                &types::Function::new(
+                    vec![create_record(1).into()],
+                    void_type(),
+                    types::CallingConvention::Target,
+                ),
+                &types::Function::new(
+                    vec![create_record(2).into()],
+                    void_type(),
+                    types::CallingConvention::Target,
+                )
+            ));
+        }
+
+        #[test]
+        fn check_functions_with_different_calling_conventions() {
+            assert!(!is_abi_compatible(
+                &Context::new(WORD_BYTES),
+                &types::Function::new(vec![], void_type(), types::CallingConvention::Source),
+                &types::Function::new(vec![], void_type(), types::CallingConvention::Target)
+            ));
+        }
+    }
+
     mod memory_class {
         use super::*;
 
@@ -231,6 +339,38 @@ mod tests {
             ));
         }
 
+        #[test]
+        fn small_nested_record() {
+            assert!(!is_memory_class(
+                &Context::new(WORD_BYTES),
+                &types::Record::new(vec![
+                    types::Primitive::Integer32.into(),
+                    types::Record::new(vec![
+                        types::Primitive::Integer32.into(),
+                        types::Primitive::Integer32.into()
+                    ])
+                    .into()
+                ])
+                .into()
+            ));
+        }
+
+        #[test]
+        fn large_nested_record() {
+            assert!(is_memory_class(
+                &Context::new(WORD_BYTES),
+                &types::Record::new(vec![
+                    types::Primitive::Integer32.into(),
+                    types::Record::new(vec![
+                        types::Primitive::Integer64.into(),
+                        types::Primitive::Integer64.into()
+                    ])
+                    .into()
+                ])
+                .into()
+            ));
+        }
+
         #[test]
         fn large_record() {
             assert!(is_memory_class(
@@ -0,0 +1,297 @@
+mod endianness;
+mod error;
+
+use super::type_size;
+use crate::{ir::*, types::Type};
+pub use endianness::*;
+pub use error::*;
+
+/// A pair of a byte offset of a word in an image and a name of a symbol
+/// referenced there.
+pub type Relocation = (usize, String);
+
+/// Evaluates a constant expression into its byte image and relocations.
+///
+/// Symbol references are emitted as zero-filled words to be relocated by
+/// backends with returned relocations. Undefined values are also filled with
+/// zeros.
+pub fn evaluate_constant(
+    expression: &Expression,
+    word_bytes: usize,
+    endianness: Endianness,
+) -> Result<(Vec<u8>, Vec<Relocation>), ConstEvalError> {
+    let mut bytes = vec![];
+    let mut relocations = vec![];
+
+    evaluate(
+        expression,
+        word_bytes,
+        endianness,
+        &mut bytes,
+        &mut relocations,
+    )?;
+
+    Ok((bytes, relocations))
+}
+
+fn evaluate(
+    expression: &Expression,
+    word_bytes: usize,
+    endianness: Endianness,
+    bytes: &mut Vec<u8>,
+    relocations: &mut Vec<Relocation>,
+) -> Result<(), ConstEvalError> {
+    match expression {
+        Expression::AlignOf(align_of) => evaluate_word(
+            type_size::calculate_alignment(align_of.type_(), word_bytes) as i64,
+            word_bytes,
+            endianness,
+            bytes,
+        ),
+        Expression::BitCast(bit_cast) => evaluate(
+            bit_cast.expression(),
+            word_bytes,
+            endianness,
+            bytes,
+            relocations,
+        )?,
+        Expression::Primitive(primitive) => {
+            evaluate_primitive(*primitive, word_bytes, endianness, bytes)
+        }
+        Expression::Record(record) => {
+            let start = bytes.len();
+
+            for (field, type_) in record.fields().iter().zip(record.type_().fields()) {
+                pad(
+                    bytes,
+                    start,
+                    type_size::calculate_alignment(type_, word_bytes),
+                );
+                evaluate(field, word_bytes, endianness, bytes, relocations)?;
+            }
+        }
+        Expression::SizeOf(size_of) => evaluate_word(
+            type_size::calculate_size(size_of.type_(), word_bytes) as i64,
+            word_bytes,
+            endianness,
+            bytes,
+        ),
+        Expression::Undefined(undefined) => fill_zeros(undefined.type_(), word_bytes, bytes),
+        Expression::Union(union) => {
+            let start = bytes.len();
+
+            if union.member_index() >= union.type_().members().len() {
+                return Err(ConstEvalError::UnionMemberIndex(union.member_index()));
+            }
+
+            evaluate(union.member(), word_bytes, endianness, bytes, relocations)?;
+
+            bytes.resize(
+                start + type_size::calculate_size(&union.type_().clone().into(), word_bytes),
+                0,
+            );
+        }
+        Expression::Variable(variable) => {
+            relocations.push((bytes.len(), variable.name().into()));
+            bytes.extend(vec![0; word_bytes]);
+        }
+        Expression::ArithmeticOperation(_)
+        | Expression::BitwiseNotOperation(_)
+        | Expression::BitwiseOperation(_)
+        | Expression::ComparisonOperation(_)
+        | Expression::PointerAddress(_)
+        | Expression::RecordAddress(_)
+        | Expression::UnionAddress(_) => {
+            return Err(ConstEvalError::NonConstantExpression(expression.clone()))
+        }
+    }
+
+    Ok(())
+}
+
+fn evaluate_primitive(
+    primitive: Primitive,
+    word_bytes: usize,
+    endianness: Endianness,
+    bytes: &mut Vec<u8>,
+) {
+    match primitive {
+        Primitive::Boolean(boolean) => bytes.push(boolean as u8),
+        Primitive::Float32(number) => bytes.extend(match endianness {
+            Endianness::Big => number.to_be_bytes(),
+            Endianness::Little => number.to_le_bytes(),
+        }),
+        Primitive::Float64(number) => bytes.extend(match endianness {
+            Endianness::Big => number.to_be_bytes(),
+            Endianness::Little => number.to_le_bytes(),
+        }),
+        Primitive::Integer8(number) => bytes.push(number),
+        Primitive::Integer32(number) => bytes.extend(match endianness {
+            Endianness::Big => number.to_be_bytes(),
+            Endianness::Little => number.to_le_bytes(),
+        }),
+        Primitive::Integer64(number) => bytes.extend(match endianness {
+            Endianness::Big => number.to_be_bytes(),
+            Endianness::Little => number.to_le_bytes(),
+        }),
+        Primitive::PointerInteger(number) => evaluate_word(number, word_bytes, endianness, bytes),
+    }
+}
+
+// Words are truncated to their least significant bytes.
+fn evaluate_word(number: i64, word_bytes: usize, endianness: Endianness, bytes: &mut Vec<u8>) {
+    match endianness {
+        Endianness::Big => bytes.extend(&number.to_be_bytes()[8 - word_bytes..]),
+        Endianness::Little => bytes.extend(&number.to_le_bytes()[..word_bytes]),
+    }
+}
+
+fn fill_zeros(type_: &Type, word_bytes: usize, bytes: &mut Vec<u8>) {
+    bytes.extend(vec![0; type_size::calculate_size(type_, word_bytes)]);
+}
+
+fn pad(bytes: &mut Vec<u8>, start: usize, alignment: usize) {
+    let offset = bytes.len() - start;
+
+    bytes.resize(start + offset.div_ceil(alignment) * alignment, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+    use pretty_assertions::assert_eq;
+
+    fn create_record() -> Expression {
+        Record::new(
+            types::Record::new(vec![
+                types::Primitive::Integer32.into(),
+                types::Primitive::Integer8.into(),
+            ]),
+            vec![
+                Primitive::Integer32(0x01020304).into(),
+                Primitive::Integer8(5).into(),
+            ],
+        )
+        .into()
+    }
+
+    #[test]
+    fn evaluate_record_in_little_endian() {
+        assert_eq!(
+            evaluate_constant(&create_record(), 8, Endianness::Little),
+            Ok((vec![4, 3, 2, 1, 5], vec![]))
+        );
+    }
+
+    #[test]
+    fn evaluate_record_in_big_endian() {
+        assert_eq!(
+            evaluate_constant(&create_record(), 8, Endianness::Big),
+            Ok((vec![1, 2, 3, 4, 5], vec![]))
+        );
+    }
+
+    #[test]
+    fn pad_record_fields() {
+        assert_eq!(
+            evaluate_constant(
+                &Record::new(
+                    types::Record::new(vec![
+                        types::Primitive::Integer8.into(),
+                        types::Primitive::Integer32.into(),
+                    ]),
+                    vec![
+                        Primitive::Integer8(1).into(),
+                        Primitive::Integer32(2).into(),
+                    ],
+                )
+                .into(),
+                8,
+                Endianness::Little
+            ),
+            Ok((vec![1, 0, 0, 0, 2, 0, 0, 0], vec![]))
+        );
+    }
+
+    #[test]
+    fn pad_union_member() {
+        assert_eq!(
+            evaluate_constant(
+                &Union::new(
+                    types::Union::new(vec![
+                        types::Primitive::Integer8.into(),
+                        types::Primitive::Integer32.into(),
+                    ]),
+                    0,
+                    Primitive::Integer8(42),
+                )
+                .into(),
+                8,
+                Endianness::Little
+            ),
+            Ok((vec![42, 0, 0, 0], vec![]))
+        );
+    }
+
+    #[test]
+    fn evaluate_pointer_integer_with_word_size() {
+        assert_eq!(
+            evaluate_constant(&Primitive::PointerInteger(-2).into(), 4, Endianness::Big),
+            Ok((vec![0xff, 0xff, 0xff, 0xfe], vec![]))
+        );
+    }
+
+    #[test]
+    fn evaluate_symbol_reference_as_placeholder() {
+        assert_eq!(
+            evaluate_constant(&Variable::new("x").into(), 8, Endianness::Little),
+            Ok((vec![0; 8], vec![(0, "x".into())]))
+        );
+    }
+
+    #[test]
+    fn evaluate_symbol_reference_in_record() {
+        let pointer_type = types::Pointer::new(types::Primitive::Integer8);
+
+        assert_eq!(
+            evaluate_constant(
+                &Record::new(
+                    types::Record::new(vec![
+                        types::Primitive::Integer8.into(),
+                        pointer_type.clone().into(),
+                        pointer_type.into(),
+                    ]),
+                    vec![
+                        Primitive::Integer8(1).into(),
+                        Variable::new("x").into(),
+                        Variable::new("y").into(),
+                    ],
+                )
+                .into(),
+                4,
+                Endianness::Little
+            ),
+            Ok((
+                vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                vec![(4, "x".into()), (8, "y".into())]
+            ))
+        );
+    }
+
+    #[test]
+    fn fail_to_evaluate_non_constant_expression() {
+        let expression = ArithmeticOperation::new(
+            types::Primitive::PointerInteger,
+            ArithmeticOperator::Add,
+            Primitive::PointerInteger(1),
+            Primitive::PointerInteger(2),
+        )
+        .into();
+
+        assert_eq!(
+            evaluate_constant(&expression, 8, Endianness::Little),
+            Err(ConstEvalError::NonConstantExpression(expression))
+        );
+    }
+}
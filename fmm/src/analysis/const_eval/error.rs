@@ -0,0 +1,19 @@
+use crate::ir::Expression;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConstEvalError {
+    NonConstantExpression(Expression),
+    UnionMemberIndex(usize),
+}
+
+impl Display for ConstEvalError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}", self)
+    }
+}
+
+impl Error for ConstEvalError {}
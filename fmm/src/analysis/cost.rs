@@ -0,0 +1,134 @@
+use crate::ir::*;
+
+pub trait TargetCostModel {
+    fn instruction_cost(&self, instruction: &Instruction) -> u32;
+}
+
+// Costs roughly relative to a simple arithmetic instruction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenericCostModel;
+
+impl TargetCostModel for GenericCostModel {
+    fn instruction_cost(&self, instruction: &Instruction) -> u32 {
+        match instruction {
+            Instruction::AllocateHeap(_)
+            | Instruction::FreeHeap(_)
+            | Instruction::ReallocateHeap(_) => 20,
+            Instruction::AtomicLoad(_)
+            | Instruction::AtomicOperation(_)
+            | Instruction::AtomicStore(_)
+            | Instruction::CompareAndSwap(_)
+            | Instruction::Fence(_) => 4,
+            Instruction::Call(call) => 5 + call.arguments().len() as u32,
+            Instruction::MemoryCopy(_) => 10,
+            Instruction::If(_) => 2,
+            Instruction::Load(_) | Instruction::Store(_) => 2,
+            Instruction::AllocateStack(_)
+            | Instruction::DeconstructRecord(_)
+            | Instruction::DeconstructUnion(_) => 1,
+        }
+    }
+}
+
+// Instructions in nested blocks are counted as well.
+pub fn function_cost(definition: &FunctionDefinition, model: &impl TargetCostModel) -> u32 {
+    block_cost(definition.body(), model)
+}
+
+pub fn is_inlinable(
+    definition: &FunctionDefinition,
+    model: &impl TargetCostModel,
+    threshold: u32,
+) -> bool {
+    function_cost(definition, model) <= threshold
+}
+
+pub(crate) fn block_cost(block: &Block, model: &impl TargetCostModel) -> u32 {
+    block
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            model.instruction_cost(instruction)
+                + match instruction {
+                    Instruction::If(if_) => {
+                        block_cost(if_.then(), model) + block_cost(if_.else_(), model)
+                    }
+                    _ => 0,
+                }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{self, void_type};
+
+    fn create_function_definition(instructions: Vec<Instruction>) -> FunctionDefinition {
+        FunctionDefinition::new(
+            "f",
+            vec![],
+            void_type(),
+            Block::new(instructions, Return::new(void_type(), void_value())),
+            Default::default(),
+        )
+    }
+
+    fn create_call(name: &str) -> Instruction {
+        Call::new(
+            types::Function::new(vec![], void_type(), types::CallingConvention::Source),
+            Variable::new("g"),
+            vec![],
+            name,
+        )
+        .into()
+    }
+
+    struct FreeCallCostModel;
+
+    impl TargetCostModel for FreeCallCostModel {
+        fn instruction_cost(&self, instruction: &Instruction) -> u32 {
+            match instruction {
+                Instruction::Call(_) => 0,
+                _ => GenericCostModel.instruction_cost(instruction),
+            }
+        }
+    }
+
+    #[test]
+    fn calculate_cost_of_empty_function() {
+        assert_eq!(
+            function_cost(&create_function_definition(vec![]), &GenericCostModel),
+            0
+        );
+    }
+
+    #[test]
+    fn calculate_cost_of_nested_blocks() {
+        assert_eq!(
+            function_cost(
+                &create_function_definition(vec![If::new(
+                    void_type(),
+                    Primitive::Boolean(true),
+                    Block::new(
+                        vec![create_call("x")],
+                        Branch::new(void_type(), void_value())
+                    ),
+                    Block::new(vec![], Branch::new(void_type(), void_value())),
+                    "y",
+                )
+                .into()]),
+                &GenericCostModel
+            ),
+            7
+        );
+    }
+
+    #[test]
+    fn inline_function_with_free_calls() {
+        let definition = create_function_definition(vec![create_call("x"), create_call("y")]);
+
+        assert!(!is_inlinable(&definition, &GenericCostModel, 8));
+        assert!(is_inlinable(&definition, &FreeCallCostModel, 8));
+    }
+}
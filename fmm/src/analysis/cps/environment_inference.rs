@@ -150,7 +150,12 @@ fn collect_from_instruction(
             collect(store.value());
             collect(store.pointer());
         }
-        Instruction::Fence(_) | Instruction::AllocateStack(_) => Default::default(),
+        Instruction::AllocateStack(allocate) => {
+            if let Some(count) = allocate.count() {
+                collect(count);
+            }
+        }
+        Instruction::Fence(_) => Default::default(),
     }
 }
 
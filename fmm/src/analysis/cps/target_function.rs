@@ -104,8 +104,7 @@ fn transform_source_function_call(
                 .iter()
                 .zip(call.type_().arguments())
                 .map(|(expression, type_)| TypedExpression::new(expression.clone(), type_.clone())),
-        )
-        .collect(),
+        ),
     )?;
     builder.add_instruction(Load::new(
         result_pointer
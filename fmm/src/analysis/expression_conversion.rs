@@ -55,7 +55,11 @@ fn convert_block(block: &mut Block, convert: &impl Fn(&mut Expression)) {
 fn convert_instruction(instruction: &mut Instruction, convert: &impl Fn(&mut Expression)) {
     match instruction {
         Instruction::AllocateHeap(allocate) => convert(allocate.size_mut()),
-        Instruction::AllocateStack(_) => {}
+        Instruction::AllocateStack(allocate) => {
+            if let Some(count) = allocate.count_mut() {
+                convert(count);
+            }
+        }
         Instruction::AtomicLoad(load) => convert(load.pointer_mut()),
         Instruction::AtomicOperation(operation) => {
             convert(operation.pointer_mut());
@@ -1,3 +1,7 @@
+mod diff;
+mod dot;
+
+pub use self::{diff::diff_modules, dot::function_to_dot};
 use crate::{
     ir::*,
     types::{self, Type},
@@ -57,11 +61,19 @@ fn format_instruction(instruction: &Instruction) -> String {
                 allocate.name()
             )
         }
-        Instruction::AllocateStack(allocate) => format!(
-            "(allocate-stack {} {})",
-            format_type(allocate.type_()),
-            allocate.name()
-        ),
+        Instruction::AllocateStack(allocate) => match allocate.count() {
+            Some(count) => format!(
+                "(allocate-stack {} {} {})",
+                format_type(allocate.type_()),
+                format_expression(count),
+                allocate.name()
+            ),
+            None => format!(
+                "(allocate-stack {} {})",
+                format_type(allocate.type_()),
+                allocate.name()
+            ),
+        },
         Instruction::AtomicLoad(load) => {
             format!(
                 "(atomic-load {} {})",
@@ -71,14 +83,18 @@ fn format_instruction(instruction: &Instruction) -> String {
         }
         Instruction::AtomicOperation(operation) => {
             format!(
-                "(atomic {} {} {} {})",
+                "(atomic {} {} {}{})",
                 match operation.operator() {
                     AtomicOperator::Add => "+",
                     AtomicOperator::Subtract => "-",
                 },
                 format_expression(operation.pointer()),
                 format_expression(operation.value()),
-                operation.name()
+                if operation.is_result_discarded() {
+                    "".into()
+                } else {
+                    " ".to_owned() + operation.name()
+                }
             )
         }
         Instruction::AtomicStore(store) => {
@@ -89,7 +105,11 @@ fn format_instruction(instruction: &Instruction) -> String {
             )
         }
         Instruction::Call(call) => format!(
-            "(call {} {} {})",
+            "({} {} {} {})",
+            match call.tail_call() {
+                TailCall::Hint => "call",
+                TailCall::MustTail => "must-tail-call",
+            },
             format_expression(call.function()),
             call.arguments()
                 .iter()
@@ -125,7 +145,15 @@ fn format_instruction(instruction: &Instruction) -> String {
         }
         Instruction::Fence(_) => "(fence)".into(),
         Instruction::FreeHeap(free) => {
-            format!("(free-heap {})", format_expression(free.pointer()))
+            if let Some(type_) = free.type_() {
+                format!(
+                    "(free-heap {} {})",
+                    format_type(type_),
+                    format_expression(free.pointer())
+                )
+            } else {
+                format!("(free-heap {})", format_expression(free.pointer()))
+            }
         }
         Instruction::If(if_) => format!(
             "(if {}\n{}\n{}\n{})",
@@ -0,0 +1,121 @@
+use super::format_module;
+use crate::ir::*;
+
+// Returns lines that differ between formatted modules, prefixed with `-` for
+// ones only in the first module and `+` for ones only in the second.
+pub fn diff_modules(one: &Module, other: &Module) -> String {
+    let one = format_module(one);
+    let other = format_module(other);
+
+    diff_lines(
+        &one.lines().collect::<Vec<_>>(),
+        &other.lines().collect::<Vec<_>>(),
+    )
+    .join("\n")
+}
+
+fn diff_lines(one: &[&str], other: &[&str]) -> Vec<String> {
+    // lengths[i][j] is the length of the longest common subsequence of
+    // one[i..] and other[j..].
+    let mut lengths = vec![vec![0; other.len() + 1]; one.len() + 1];
+
+    for i in (0..one.len()).rev() {
+        for j in (0..other.len()).rev() {
+            lengths[i][j] = if one[i] == other[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < one.len() && j < other.len() {
+        if one[i] == other[j] {
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(format!("-{}", one[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", other[j]));
+            j += 1;
+        }
+    }
+
+    lines.extend(one[i..].iter().map(|line| format!("-{}", line)));
+    lines.extend(other[j..].iter().map(|line| format!("+{}", line)));
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+    use pretty_assertions::assert_eq;
+
+    fn create_module(instructions: Vec<Instruction>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new(
+                    "x",
+                    types::Pointer::new(types::Primitive::PointerInteger),
+                )],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    instructions,
+                    Return::new(types::Primitive::PointerInteger, Variable::new("y")),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    #[test]
+    fn diff_same_modules() {
+        let module = create_module(vec![]);
+
+        assert_eq!(diff_modules(&module, &module), "");
+    }
+
+    #[test]
+    fn diff_modules_with_different_instruction() {
+        assert_eq!(
+            diff_modules(
+                &create_module(vec![
+                    Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+                    Load::new(types::Primitive::PointerInteger, Variable::new("x"), "z").into(),
+                ]),
+                &create_module(vec![
+                    Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+                    Load::new(types::Primitive::PointerInteger, Variable::new("y"), "z").into(),
+                ])
+            ),
+            "-      (load x z)\n+      (load y z)"
+        );
+    }
+
+    #[test]
+    fn diff_modules_with_extra_instruction() {
+        assert_eq!(
+            diff_modules(
+                &create_module(vec![]),
+                &create_module(vec![Load::new(
+                    types::Primitive::PointerInteger,
+                    Variable::new("x"),
+                    "y"
+                )
+                .into()])
+            ),
+            "+      (load x y)"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use super::{format_expression, format_instruction, format_terminal_instruction};
+use crate::ir::*;
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Vec<String>>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    fn add_node(&mut self) -> usize {
+        self.nodes.push(vec![]);
+        self.nodes.len() - 1
+    }
+}
+
+// Returns a control flow graph in the DOT language of Graphviz. Blocks are
+// split at if instructions and branches of them join into new nodes.
+pub fn function_to_dot(definition: &FunctionDefinition) -> String {
+    let mut graph = Graph::default();
+
+    render_block(definition.body(), &mut graph);
+
+    format!(
+        "digraph {:?} {{\n  node [shape=box];\n{}}}\n",
+        definition.name(),
+        graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, lines)| format!(
+                "  {} [label={:?}];\n",
+                index,
+                lines
+                    .iter()
+                    .map(|line| line.clone() + "\n")
+                    .collect::<String>()
+            ))
+            .chain(
+                graph
+                    .edges
+                    .iter()
+                    .map(|(source, target)| format!("  {} -> {};\n", source, target))
+            )
+            .collect::<String>()
+    )
+}
+
+// Returns a node at the end of a block if it branches to its parent.
+fn render_block(block: &Block, graph: &mut Graph) -> (usize, Option<usize>) {
+    let entry = graph.add_node();
+    let mut current = entry;
+
+    for instruction in block.instructions() {
+        if let Instruction::If(if_) = instruction {
+            graph.nodes[current].push(format!("(if {})", format_expression(if_.condition())));
+
+            let join = graph.add_node();
+
+            for block in [if_.then(), if_.else_()] {
+                let (entry, exit) = render_block(block, graph);
+
+                graph.edges.push((current, entry));
+
+                if let Some(exit) = exit {
+                    graph.edges.push((exit, join));
+                }
+            }
+
+            current = join;
+        } else {
+            graph.nodes[current].push(format_instruction(instruction));
+        }
+    }
+
+    graph.nodes[current].push(format_terminal_instruction(block.terminal_instruction()));
+
+    (
+        entry,
+        if block.terminal_instruction().is_branch() {
+            Some(current)
+        } else {
+            None
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{self, void_type};
+    use pretty_assertions::assert_eq;
+
+    fn create_function_definition(instructions: Vec<Instruction>) -> FunctionDefinition {
+        FunctionDefinition::new(
+            "f",
+            vec![],
+            void_type(),
+            Block::new(instructions, Return::new(void_type(), void_value())),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn render_empty_function() {
+        assert_eq!(
+            function_to_dot(&create_function_definition(vec![])),
+            "digraph \"f\" {\n  node [shape=box];\n  0 [label=\"(return (record))\\n\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn render_diamond() {
+        let dot = function_to_dot(&create_function_definition(vec![If::new(
+            types::Primitive::PointerInteger,
+            Primitive::Boolean(true),
+            Block::new(
+                vec![],
+                Branch::new(
+                    types::Primitive::PointerInteger,
+                    Primitive::PointerInteger(1),
+                ),
+            ),
+            Block::new(
+                vec![],
+                Branch::new(
+                    types::Primitive::PointerInteger,
+                    Primitive::PointerInteger(2),
+                ),
+            ),
+            "x",
+        )
+        .into()]));
+
+        assert_eq!(dot.matches("[label=").count(), 4);
+        assert_eq!(
+            dot.lines()
+                .filter(|line| line.contains("->"))
+                .collect::<Vec<_>>(),
+            vec!["  0 -> 2;", "  2 -> 1;", "  0 -> 3;", "  3 -> 1;"]
+        );
+    }
+
+    #[test]
+    fn render_if_with_return() {
+        let dot = function_to_dot(&create_function_definition(vec![If::new(
+            void_type(),
+            Primitive::Boolean(true),
+            Block::new(vec![], Return::new(void_type(), void_value())),
+            Block::new(vec![], Branch::new(void_type(), void_value())),
+            "x",
+        )
+        .into()]));
+
+        assert_eq!(
+            dot.lines()
+                .filter(|line| line.contains("->"))
+                .collect::<Vec<_>>(),
+            vec!["  0 -> 2;", "  0 -> 3;", "  3 -> 1;"]
+        );
+    }
+}
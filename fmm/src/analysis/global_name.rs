@@ -0,0 +1,60 @@
+use crate::ir::*;
+use fnv::FnvHashSet;
+
+pub fn collect(module: &Module) -> FnvHashSet<String> {
+    module
+        .variable_declarations()
+        .iter()
+        .map(|declaration| declaration.name())
+        .chain(
+            module
+                .function_declarations()
+                .iter()
+                .map(|declaration| declaration.name()),
+        )
+        .chain(
+            module
+                .variable_definitions()
+                .iter()
+                .map(|definition| definition.name()),
+        )
+        .chain(
+            module
+                .function_definitions()
+                .iter()
+                .map(|definition| definition.name()),
+        )
+        .map(String::from)
+        .collect()
+}
+
+// Generated names are added into given names.
+pub fn generate(prefix: &str, names: &mut FnvHashSet<String>) -> String {
+    let name = (0..)
+        .map(|index| format!("{}_{}", prefix, index))
+        .find(|name| !names.contains(name))
+        .unwrap();
+
+    names.insert(name.clone());
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn generate_name() {
+        assert_eq!(generate("f", &mut Default::default()), "f_0");
+    }
+
+    #[test]
+    fn generate_name_different_from_existing_ones() {
+        let mut names = ["f_0".to_owned(), "f_1".to_owned()].into_iter().collect();
+
+        assert_eq!(generate("f", &mut names), "f_2");
+        assert_eq!(generate("f", &mut names), "f_3");
+    }
+}
@@ -0,0 +1,362 @@
+use super::{
+    cost::{self, TargetCostModel},
+    expression_conversion, rename,
+};
+use crate::ir::*;
+use fnv::FnvHashMap;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+// Merge function definitions which are identical except for names of their
+// local variables. Duplicates with internal linkage are removed while the
+// other ones are replaced with calls to their representatives unless the calls
+// cost more than the original bodies in a target cost model. References to the
+// latter are kept to preserve their identities as function pointers.
+pub fn transform(module: &mut Module, model: &impl TargetCostModel) {
+    // Representatives are bucketed by hashes of their normalized definitions
+    // and compared structurally only within the buckets.
+    let mut representatives = FnvHashMap::<u64, Vec<(FunctionDefinition, &str)>>::default();
+    let mut names = FnvHashMap::<String, String>::default();
+
+    for definition in module.function_definitions() {
+        let normalized = normalize(definition);
+        let bucket = representatives.entry(hash(&normalized)).or_default();
+
+        if let Some((_, name)) = bucket
+            .iter()
+            .find(|(representative, _)| representative == &normalized)
+        {
+            names.insert(definition.name().into(), (*name).into());
+        } else {
+            bucket.push((normalized, definition.name()));
+        }
+    }
+
+    if names.is_empty() {
+        return;
+    }
+
+    let internal_names = module
+        .function_definitions()
+        .iter()
+        .filter(|definition| definition.options().linkage() == Linkage::Internal)
+        .filter_map(|definition| {
+            Some((
+                definition.name().to_owned(),
+                names.get(definition.name())?.clone(),
+            ))
+        })
+        .collect::<FnvHashMap<_, _>>();
+
+    module
+        .function_definitions_mut()
+        .retain(|definition| !internal_names.contains_key(definition.name()));
+
+    expression_conversion::convert(module, &|expression| match expression {
+        Expression::Variable(variable) => internal_names
+            .get(variable.name())
+            .map(|name| Variable::new(name).into())
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    });
+
+    for definition in module.function_definitions_mut() {
+        if let Some(name) = names.get(definition.name()) {
+            let body = create_thunk_body(definition, name);
+
+            if cost::block_cost(&body, model) <= cost::block_cost(definition.body(), model) {
+                *definition.body_mut() = body;
+            }
+        }
+    }
+}
+
+fn normalize(definition: &FunctionDefinition) -> FunctionDefinition {
+    let mut local_names = definition
+        .arguments()
+        .iter()
+        .map(|argument| argument.name())
+        .collect::<Vec<_>>();
+
+    collect_local_names(definition.body(), &mut local_names);
+
+    let names = local_names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| (name, index.to_string()))
+        .chain([(definition.name(), "".into())])
+        .collect::<FnvHashMap<_, _>>();
+
+    rename::rename_function_definition(definition, &|name| {
+        names.get(name).cloned().unwrap_or_else(|| name.into())
+    })
+}
+
+// IR nodes do not implement `Hash` because of floating-point numbers, so
+// their debug representations are hashed instead.
+fn hash(definition: &FunctionDefinition) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    format!("{:?}", definition).hash(&mut hasher);
+
+    hasher.finish()
+}
+
+fn collect_local_names<'a>(block: &'a Block, names: &mut Vec<&'a str>) {
+    for instruction in block.instructions() {
+        if let Some((name, _)) = instruction.value() {
+            names.push(name);
+        }
+
+        if let Instruction::If(if_) = instruction {
+            collect_local_names(if_.then(), names);
+            collect_local_names(if_.else_(), names);
+        }
+    }
+}
+
+fn create_thunk_body(definition: &FunctionDefinition, name: &str) -> Block {
+    let mut result_name = format!("{}_result", definition.name());
+
+    while definition
+        .arguments()
+        .iter()
+        .any(|argument| argument.name() == result_name)
+    {
+        result_name.push('_');
+    }
+
+    Block::new(
+        vec![Call::new(
+            definition.type_(),
+            Variable::new(name),
+            definition
+                .arguments()
+                .iter()
+                .map(|argument| Variable::new(argument.name()).into())
+                .collect(),
+            &result_name,
+        )
+        .into()],
+        Return::new(definition.result_type().clone(), Variable::new(result_name)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::{cost::GenericCostModel, validation},
+        types,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn create_function_definition(
+        name: &str,
+        argument_name: &str,
+        linkage: Linkage,
+    ) -> FunctionDefinition {
+        FunctionDefinition::new(
+            name,
+            vec![Argument::new(
+                argument_name,
+                types::Primitive::PointerInteger,
+            )],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![],
+                Return::new(
+                    types::Primitive::PointerInteger,
+                    ArithmeticOperation::new(
+                        types::Primitive::PointerInteger,
+                        ArithmeticOperator::Add,
+                        Variable::new(argument_name),
+                        Primitive::PointerInteger(1),
+                    ),
+                ),
+            ),
+            FunctionDefinitionOptions::new().set_linkage(linkage),
+        )
+    }
+
+    fn create_caller(name: &str) -> FunctionDefinition {
+        FunctionDefinition::new(
+            "h",
+            vec![],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![Call::new(
+                    types::Function::new(
+                        vec![types::Primitive::PointerInteger.into()],
+                        types::Primitive::PointerInteger,
+                        types::CallingConvention::Source,
+                    ),
+                    Variable::new(name),
+                    vec![Primitive::PointerInteger(42).into()],
+                    "x",
+                )
+                .into()],
+                Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+            ),
+            Default::default(),
+        )
+    }
+
+    struct FreeCallCostModel;
+
+    impl TargetCostModel for FreeCallCostModel {
+        fn instruction_cost(&self, instruction: &Instruction) -> u32 {
+            match instruction {
+                Instruction::Call(_) => 0,
+                _ => GenericCostModel.instruction_cost(instruction),
+            }
+        }
+    }
+
+    fn transform_module(module: Module) -> Module {
+        transform_module_with_model(module, &FreeCallCostModel)
+    }
+
+    fn transform_module_with_model(mut module: Module, model: &impl TargetCostModel) -> Module {
+        validation::validate(&module).unwrap();
+
+        transform(&mut module, model);
+
+        validation::validate(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn merge_identical_functions() {
+        assert_eq!(
+            transform_module(Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![
+                    create_function_definition("f", "x", Linkage::Internal),
+                    create_function_definition("g", "y", Linkage::Internal),
+                    create_caller("g"),
+                ],
+            )),
+            Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![
+                    create_function_definition("f", "x", Linkage::Internal),
+                    create_caller("f"),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn replace_external_duplicate_with_thunk() {
+        let module = transform_module(Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_function_definition("f", "x", Linkage::External),
+                create_function_definition("g", "y", Linkage::External),
+            ],
+        ));
+
+        assert_eq!(
+            module.function_definitions()[1].body(),
+            &Block::new(
+                vec![Call::new(
+                    module.function_definitions()[1].type_(),
+                    Variable::new("f"),
+                    vec![Variable::new("y").into()],
+                    "g_result",
+                )
+                .into()],
+                Return::new(types::Primitive::PointerInteger, Variable::new("g_result")),
+            )
+        );
+    }
+
+    #[test]
+    fn keep_references_to_external_duplicate() {
+        let module = transform_module(Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_function_definition("f", "x", Linkage::External),
+                create_function_definition("g", "y", Linkage::External),
+                create_caller("g"),
+            ],
+        ));
+
+        assert_eq!(module.function_definitions()[2], create_caller("g"));
+    }
+
+    #[test]
+    fn keep_external_duplicate_cheaper_than_thunk() {
+        let module = transform_module_with_model(
+            Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![
+                    create_function_definition("f", "x", Linkage::External),
+                    create_function_definition("g", "y", Linkage::External),
+                ],
+            ),
+            &GenericCostModel,
+        );
+
+        assert_eq!(
+            module.function_definitions()[1],
+            create_function_definition("g", "y", Linkage::External)
+        );
+    }
+
+    #[test]
+    fn generate_thunk_result_name_different_from_argument_names() {
+        let module = transform_module(Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_function_definition("f", "x", Linkage::External),
+                create_function_definition("g", "g_result", Linkage::External),
+            ],
+        ));
+
+        assert_eq!(
+            module.function_definitions()[1].body(),
+            &Block::new(
+                vec![Call::new(
+                    module.function_definitions()[1].type_(),
+                    Variable::new("f"),
+                    vec![Variable::new("g_result").into()],
+                    "g_result_",
+                )
+                .into()],
+                Return::new(types::Primitive::PointerInteger, Variable::new("g_result_")),
+            )
+        );
+    }
+
+    #[test]
+    fn keep_functions_with_different_linkage() {
+        let module = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_function_definition("f", "x", Linkage::Internal),
+                create_function_definition("g", "y", Linkage::External),
+            ],
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -0,0 +1,236 @@
+use super::expression_conversion;
+use crate::ir::*;
+use fnv::FnvHashMap;
+use std::mem::{replace, take};
+
+// Inline taken branches of if instructions with constant conditions. Results of
+// the if instructions are replaced with values of their branch instructions.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_block(definition.body_mut(), &mut Default::default());
+    }
+}
+
+fn transform_block(block: &mut Block, values: &mut FnvHashMap<String, Expression>) {
+    let mut instructions = Vec::with_capacity(block.instructions().len());
+
+    for mut instruction in take(block.instructions_mut()) {
+        expression_conversion::convert_in_instruction(&mut instruction, &|expression| {
+            replace_value(expression, values)
+        });
+
+        if let Instruction::If(if_) = &mut instruction {
+            transform_block(if_.then_mut(), values);
+            transform_block(if_.else_mut(), values);
+
+            if let Expression::Primitive(Primitive::Boolean(condition)) = *if_.condition() {
+                let taken = replace(
+                    if condition {
+                        if_.then_mut()
+                    } else {
+                        if_.else_mut()
+                    },
+                    Block::new(vec![], TerminalInstruction::Unreachable),
+                );
+
+                instructions.extend(taken.instructions().iter().cloned());
+
+                match taken.terminal_instruction() {
+                    TerminalInstruction::Branch(branch) => {
+                        values.insert(if_.name().into(), branch.expression().clone());
+                        continue;
+                    }
+                    // Instructions after the if instruction are unreachable.
+                    terminal_instruction => {
+                        *block.instructions_mut() = instructions;
+                        *block.terminal_instruction_mut() = terminal_instruction.clone();
+                        return;
+                    }
+                }
+            }
+        }
+
+        instructions.push(instruction);
+    }
+
+    *block.instructions_mut() = instructions;
+
+    expression_conversion::convert_in_terminal_instruction(
+        block.terminal_instruction_mut(),
+        &|expression| replace_value(expression, values),
+    );
+}
+
+fn replace_value(expression: &Expression, values: &FnvHashMap<String, Expression>) -> Expression {
+    match expression {
+        Expression::Variable(variable) => values
+            .get(variable.name())
+            .cloned()
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::type_check, types};
+    use pretty_assertions::assert_eq;
+
+    fn create_module(instructions: Vec<Instruction>, result: impl Into<Expression>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new(
+                    "p",
+                    types::Pointer::new(types::Primitive::PointerInteger),
+                )],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    instructions,
+                    Return::new(types::Primitive::PointerInteger, result),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn create_if(condition: impl Into<Expression>, then: Block, else_: Block) -> Instruction {
+        If::new(
+            types::Primitive::PointerInteger,
+            condition,
+            then,
+            else_,
+            "x",
+        )
+        .into()
+    }
+
+    fn create_load(name: &str) -> Instruction {
+        Load::new(types::Primitive::PointerInteger, Variable::new("p"), name).into()
+    }
+
+    fn create_branch_block(instructions: Vec<Instruction>, name: &str) -> Block {
+        Block::new(
+            instructions,
+            Branch::new(types::Primitive::PointerInteger, Variable::new(name)),
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn simplify_if_with_true_condition() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![create_if(
+                    Primitive::Boolean(true),
+                    create_branch_block(vec![create_load("y")], "y"),
+                    create_branch_block(vec![create_load("z")], "z"),
+                )],
+                Variable::new("x"),
+            )),
+            create_module(vec![create_load("y")], Variable::new("y"))
+        );
+    }
+
+    #[test]
+    fn simplify_if_with_false_condition() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![create_if(
+                    Primitive::Boolean(false),
+                    create_branch_block(vec![create_load("y")], "y"),
+                    create_branch_block(vec![create_load("z")], "z"),
+                )],
+                Variable::new("x"),
+            )),
+            create_module(vec![create_load("z")], Variable::new("z"))
+        );
+    }
+
+    #[test]
+    fn simplify_nested_if() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![create_if(
+                    Primitive::Boolean(true),
+                    create_branch_block(
+                        vec![If::new(
+                            types::Primitive::PointerInteger,
+                            Primitive::Boolean(false),
+                            create_branch_block(vec![create_load("y")], "y"),
+                            create_branch_block(vec![create_load("z")], "z"),
+                            "w",
+                        )
+                        .into()],
+                        "w",
+                    ),
+                    Block::new(
+                        vec![],
+                        Branch::new(
+                            types::Primitive::PointerInteger,
+                            Primitive::PointerInteger(0),
+                        ),
+                    ),
+                )],
+                Variable::new("x"),
+            )),
+            create_module(vec![create_load("z")], Variable::new("z"))
+        );
+    }
+
+    #[test]
+    fn simplify_if_with_return() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![
+                    create_if(
+                        Primitive::Boolean(true),
+                        Block::new(
+                            vec![create_load("y")],
+                            Return::new(types::Primitive::PointerInteger, Variable::new("y")),
+                        ),
+                        create_branch_block(vec![create_load("z")], "z"),
+                    ),
+                    create_load("w"),
+                ],
+                Variable::new("w"),
+            )),
+            create_module(vec![create_load("y")], Variable::new("y"))
+        );
+    }
+
+    #[test]
+    fn keep_if_with_non_constant_condition() {
+        let module = create_module(
+            vec![
+                create_load("v"),
+                create_if(
+                    ComparisonOperation::new(
+                        types::Primitive::PointerInteger,
+                        ComparisonOperator::Equal,
+                        Variable::new("v"),
+                        Primitive::PointerInteger(0),
+                    ),
+                    create_branch_block(vec![create_load("y")], "y"),
+                    create_branch_block(vec![create_load("z")], "z"),
+                ),
+            ],
+            Variable::new("x"),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::ir::*;
+
+pub fn annotate(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        let leaf = is_leaf_function(definition);
+
+        *definition.options_mut() = definition.options().clone().set_leaf(leaf);
+    }
+}
+
+pub fn is_leaf_function(definition: &FunctionDefinition) -> bool {
+    is_leaf_block(definition.body())
+}
+
+// Heap operations and memory copies are lowered to calls of library functions.
+fn is_leaf_block(block: &Block) -> bool {
+    block
+        .instructions()
+        .iter()
+        .all(|instruction| match instruction {
+            Instruction::AllocateHeap(_)
+            | Instruction::Call(_)
+            | Instruction::FreeHeap(_)
+            | Instruction::MemoryCopy(_)
+            | Instruction::ReallocateHeap(_) => false,
+            Instruction::If(if_) => is_leaf_block(if_.then()) && is_leaf_block(if_.else_()),
+            _ => true,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{self, void_type};
+
+    fn create_function_definition(instructions: Vec<Instruction>) -> FunctionDefinition {
+        FunctionDefinition::new(
+            "f",
+            vec![],
+            void_type(),
+            Block::new(instructions, Return::new(void_type(), void_value())),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn check_function_without_call() {
+        assert!(is_leaf_function(&create_function_definition(vec![
+            AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+            Store::new(
+                types::Primitive::PointerInteger,
+                Primitive::PointerInteger(42),
+                Variable::new("x"),
+            )
+            .into(),
+        ])));
+    }
+
+    #[test]
+    fn check_function_with_library_calls() {
+        for instruction in [
+            AllocateHeap::new(Primitive::PointerInteger(42), "x").into(),
+            FreeHeap::new(Undefined::new(types::generic_pointer_type())).into(),
+            MemoryCopy::new(
+                Undefined::new(types::generic_pointer_type()),
+                Undefined::new(types::generic_pointer_type()),
+                Primitive::PointerInteger(42),
+            )
+            .into(),
+            ReallocateHeap::new(
+                Undefined::new(types::generic_pointer_type()),
+                Primitive::PointerInteger(42),
+                "x",
+            )
+            .into(),
+        ] {
+            assert!(!is_leaf_function(&create_function_definition(vec![
+                instruction
+            ])));
+        }
+    }
+
+    #[test]
+    fn check_function_with_call_in_if() {
+        assert!(!is_leaf_function(&create_function_definition(vec![
+            If::new(
+                void_type(),
+                Primitive::Boolean(true),
+                Block::new(vec![], Branch::new(void_type(), void_value())),
+                Block::new(
+                    vec![Call::new(
+                        types::Function::new(vec![], void_type(), types::CallingConvention::Source),
+                        Variable::new("g"),
+                        vec![],
+                        "x",
+                    )
+                    .into()],
+                    Branch::new(void_type(), void_value()),
+                ),
+                "y",
+            )
+            .into()
+        ])));
+    }
+
+    #[test]
+    fn annotate_leaf_function() {
+        let mut module = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![create_function_definition(vec![])],
+        );
+
+        annotate(&mut module);
+
+        assert!(module.function_definitions()[0].options().is_leaf());
+    }
+}
@@ -0,0 +1,387 @@
+mod error;
+mod strategy;
+
+use super::rename;
+use crate::ir::*;
+pub use error::*;
+use fnv::{FnvHashMap, FnvHashSet};
+pub use strategy::*;
+
+// Definitions with internal linkage are renamed on conflicts. Strategies
+// resolve conflicts between the other definitions.
+pub fn merge(one: &Module, other: &Module, strategy: MergeStrategy) -> Result<Module, MergeError> {
+    let mut names = collect_global_names(one)
+        .union(&collect_global_names(other))
+        .map(|&name| name.to_owned())
+        .collect::<FnvHashSet<_>>();
+    let other = rename_internal_definitions(other, &collect_global_names(one), &mut names);
+    let one = rename_internal_definitions(one, &collect_global_names(&other), &mut names);
+
+    merge_modules(&one, &other, strategy)
+}
+
+fn merge_modules(
+    one: &Module,
+    other: &Module,
+    strategy: MergeStrategy,
+) -> Result<Module, MergeError> {
+    let variable_definitions = merge_definitions(
+        one.variable_definitions(),
+        other.variable_definitions(),
+        |definition| definition.name(),
+        |definition| definition.options().linkage(),
+        strategy,
+    )?;
+    let function_definitions = merge_definitions(
+        one.function_definitions(),
+        other.function_definitions(),
+        |definition| definition.name(),
+        |definition| definition.options().linkage(),
+        strategy,
+    )?;
+
+    Ok(Module::new(
+        merge_declarations(
+            one.variable_declarations(),
+            other.variable_declarations(),
+            |declaration| declaration.name(),
+        ),
+        merge_declarations(
+            one.function_declarations(),
+            other.function_declarations(),
+            |declaration| declaration.name(),
+        ),
+        variable_definitions,
+        function_definitions,
+    ))
+}
+
+fn collect_global_names(module: &Module) -> FnvHashSet<&str> {
+    module
+        .variable_declarations()
+        .iter()
+        .map(|declaration| declaration.name())
+        .chain(
+            module
+                .function_declarations()
+                .iter()
+                .map(|declaration| declaration.name()),
+        )
+        .chain(
+            module
+                .variable_definitions()
+                .iter()
+                .map(|definition| definition.name()),
+        )
+        .chain(
+            module
+                .function_definitions()
+                .iter()
+                .map(|definition| definition.name()),
+        )
+        .collect()
+}
+
+fn rename_internal_definitions(
+    module: &Module,
+    conflicting_names: &FnvHashSet<&str>,
+    names: &mut FnvHashSet<String>,
+) -> Module {
+    let mut new_names = FnvHashMap::<String, String>::default();
+
+    for name in module
+        .variable_definitions()
+        .iter()
+        .filter(|definition| definition.options().linkage() == Linkage::Internal)
+        .map(|definition| definition.name())
+        .chain(
+            module
+                .function_definitions()
+                .iter()
+                .filter(|definition| definition.options().linkage() == Linkage::Internal)
+                .map(|definition| definition.name()),
+        )
+        .filter(|name| conflicting_names.contains(name))
+    {
+        let new_name = (1..)
+            .map(|index| format!("{}_{}", name, index))
+            .find(|name| !names.contains(name))
+            .unwrap();
+
+        names.insert(new_name.clone());
+        new_names.insert(name.into(), new_name);
+    }
+
+    rename::rename(module, |name| {
+        new_names.get(name).cloned().unwrap_or_else(|| name.into())
+    })
+}
+
+fn merge_declarations<T: Clone>(ones: &[T], others: &[T], name: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut names = FnvHashSet::default();
+
+    ones.iter()
+        .chain(others)
+        .filter(|declaration| names.insert(name(declaration)))
+        .cloned()
+        .collect()
+}
+
+fn merge_definitions<T: Clone>(
+    ones: &[T],
+    others: &[T],
+    name: impl Fn(&T) -> &str,
+    linkage: impl Fn(&T) -> Linkage,
+    strategy: MergeStrategy,
+) -> Result<Vec<T>, MergeError> {
+    let mut definitions = ones.to_vec();
+
+    for other in others {
+        let Some(one) = definitions
+            .iter_mut()
+            .find(|definition| name(definition) == name(other))
+        else {
+            definitions.push(other.clone());
+            continue;
+        };
+
+        let keeps_second = match (
+            linkage(one) == Linkage::Weak,
+            linkage(other) == Linkage::Weak,
+        ) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => match strategy {
+                MergeStrategy::KeepFirst => false,
+                MergeStrategy::KeepSecond => true,
+                MergeStrategy::Error => {
+                    return Err(MergeError::DuplicateDefinition(name(other).into()))
+                }
+            },
+        };
+
+        if keeps_second {
+            *one = other.clone();
+        }
+    }
+
+    Ok(definitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+    use pretty_assertions::assert_eq;
+
+    fn create_module(linkage: Linkage, result: i64) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    vec![],
+                    Return::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(result),
+                    ),
+                ),
+                FunctionDefinitionOptions::new().set_linkage(linkage),
+            )],
+        )
+    }
+
+    #[test]
+    fn merge_modules_without_conflict() {
+        let declaration = FunctionDeclaration::new(
+            "g",
+            types::Function::new(
+                vec![],
+                types::Primitive::PointerInteger,
+                types::CallingConvention::Target,
+            ),
+        );
+        let one = Module::new(vec![], vec![declaration.clone()], vec![], vec![]);
+        let other = Module::new(
+            vec![],
+            vec![declaration.clone()],
+            vec![],
+            create_module(Linkage::External, 1)
+                .function_definitions()
+                .to_vec(),
+        );
+
+        assert_eq!(
+            merge(&one, &other, MergeStrategy::Error),
+            Ok(Module::new(
+                vec![],
+                vec![declaration],
+                vec![],
+                other.function_definitions().to_vec(),
+            ))
+        );
+    }
+
+    #[test]
+    fn keep_first_definition() {
+        assert_eq!(
+            merge(
+                &create_module(Linkage::External, 1),
+                &create_module(Linkage::External, 2),
+                MergeStrategy::KeepFirst
+            ),
+            Ok(create_module(Linkage::External, 1))
+        );
+    }
+
+    #[test]
+    fn keep_second_definition() {
+        assert_eq!(
+            merge(
+                &create_module(Linkage::External, 1),
+                &create_module(Linkage::External, 2),
+                MergeStrategy::KeepSecond
+            ),
+            Ok(create_module(Linkage::External, 2))
+        );
+    }
+
+    #[test]
+    fn fail_to_merge_duplicate_definitions() {
+        assert_eq!(
+            merge(
+                &create_module(Linkage::External, 1),
+                &create_module(Linkage::External, 2),
+                MergeStrategy::Error
+            ),
+            Err(MergeError::DuplicateDefinition("f".into()))
+        );
+    }
+
+    #[test]
+    fn rename_conflicting_internal_definitions() {
+        for strategy in [
+            MergeStrategy::KeepFirst,
+            MergeStrategy::KeepSecond,
+            MergeStrategy::Error,
+        ] {
+            assert_eq!(
+                merge(
+                    &create_module(Linkage::Internal, 1),
+                    &create_module(Linkage::Internal, 2),
+                    strategy
+                ),
+                Ok(Module::new(
+                    vec![],
+                    vec![],
+                    vec![],
+                    create_module(Linkage::Internal, 1)
+                        .function_definitions()
+                        .iter()
+                        .cloned()
+                        .chain(
+                            rename::rename(&create_module(Linkage::Internal, 2), |_| "f_1".into())
+                                .function_definitions()
+                                .to_vec()
+                        )
+                        .collect(),
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn rename_internal_definition_conflicting_with_external_one() {
+        let module = merge(
+            &create_module(Linkage::Internal, 1),
+            &create_module(Linkage::External, 2),
+            MergeStrategy::Error,
+        )
+        .unwrap();
+
+        assert_eq!(
+            module
+                .function_definitions()
+                .iter()
+                .map(|definition| definition.name())
+                .collect::<Vec<_>>(),
+            vec!["f_1", "f"]
+        );
+        assert_eq!(
+            module.function_definitions()[1],
+            create_module(Linkage::External, 2).function_definitions()[0]
+        );
+    }
+
+    #[test]
+    fn rename_references_to_internal_definitions() {
+        let function_type = types::Function::new(
+            vec![],
+            types::Primitive::PointerInteger,
+            types::CallingConvention::Target,
+        );
+        let other = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_module(Linkage::Internal, 2).function_definitions()[0].clone(),
+                FunctionDefinition::new(
+                    "g",
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    Block::new(
+                        vec![
+                            Call::new(function_type.clone(), Variable::new("f"), vec![], "x")
+                                .into(),
+                        ],
+                        Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+                    ),
+                    Default::default(),
+                ),
+            ],
+        );
+
+        let module = merge(
+            &create_module(Linkage::External, 1),
+            &other,
+            MergeStrategy::Error,
+        )
+        .unwrap();
+
+        assert_eq!(
+            module.function_definitions()[2].body().instructions()[0],
+            Call::new(function_type, Variable::new("f_1"), vec![], "x").into()
+        );
+    }
+
+    #[test]
+    fn keep_strong_definition_over_weak_one() {
+        for strategy in [
+            MergeStrategy::KeepFirst,
+            MergeStrategy::KeepSecond,
+            MergeStrategy::Error,
+        ] {
+            assert_eq!(
+                merge(
+                    &create_module(Linkage::Weak, 1),
+                    &create_module(Linkage::External, 2),
+                    strategy
+                ),
+                Ok(create_module(Linkage::External, 2))
+            );
+            assert_eq!(
+                merge(
+                    &create_module(Linkage::External, 1),
+                    &create_module(Linkage::Weak, 2),
+                    strategy
+                ),
+                Ok(create_module(Linkage::External, 1))
+            );
+        }
+    }
+}
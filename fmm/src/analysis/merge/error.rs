@@ -0,0 +1,17 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    DuplicateDefinition(String),
+}
+
+impl Display for MergeError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}", self)
+    }
+}
+
+impl Error for MergeError {}
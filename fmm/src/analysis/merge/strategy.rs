@@ -0,0 +1,8 @@
+// Strategies for definitions of the same name and linkage strength. Strong
+// definitions always take precedence over weak ones.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MergeStrategy {
+    KeepFirst,
+    KeepSecond,
+    Error,
+}
@@ -0,0 +1,523 @@
+use super::{
+    alias,
+    cost::{self, TargetCostModel},
+    global_name, local_variable,
+};
+use crate::{
+    ir::*,
+    types::{void_type, Type},
+};
+use fnv::{FnvHashMap, FnvHashSet};
+use std::mem::replace;
+
+// Extract branches of if instructions whose costs in a target cost model exceed
+// a threshold into internal functions which take variables used in the
+// branches as arguments. Branches containing returns from functions or pointers
+// to their own stack allocations escaping from them are not extracted.
+pub fn transform(module: &mut Module, model: &impl TargetCostModel, threshold: u32) {
+    let mut names = global_name::collect(module);
+    let mut definitions = vec![];
+
+    for definition in module.function_definitions_mut() {
+        let variables = local_variable::collect(definition)
+            .into_iter()
+            .map(|(name, type_)| (name.to_owned(), type_))
+            .collect::<FnvHashMap<_, _>>();
+        let mut context = Context {
+            name: definition.name().into(),
+            model,
+            threshold,
+            variables,
+            names: &mut names,
+            definitions: &mut definitions,
+        };
+
+        transform_block(&mut context, definition.body_mut());
+    }
+
+    module.function_definitions_mut().extend(definitions);
+}
+
+struct Context<'a, M> {
+    name: String,
+    model: &'a M,
+    threshold: u32,
+    variables: FnvHashMap<String, Type>,
+    names: &'a mut FnvHashSet<String>,
+    definitions: &'a mut Vec<FunctionDefinition>,
+}
+
+fn transform_block(context: &mut Context<impl TargetCostModel>, block: &mut Block) {
+    for instruction in block.instructions_mut() {
+        if let Instruction::If(if_) = instruction {
+            let type_ = if_.type_().clone();
+
+            transform_branch(context, if_.then_mut(), &type_);
+            transform_branch(context, if_.else_mut(), &type_);
+        }
+    }
+}
+
+fn transform_branch(context: &mut Context<impl TargetCostModel>, block: &mut Block, type_: &Type) {
+    if block.terminal_instruction().is_branch()
+        && !has_return(block)
+        && !has_escaping_stack_allocation(block)
+        && cost::block_cost(block, context.model) > context.threshold
+    {
+        outline_block(context, block, type_);
+    } else {
+        transform_block(context, block);
+    }
+}
+
+fn has_return(block: &Block) -> bool {
+    matches!(block.terminal_instruction(), TerminalInstruction::Return(_))
+        || block
+            .instructions()
+            .iter()
+            .any(|instruction| match instruction {
+                Instruction::If(if_) => has_return(if_.then()) || has_return(if_.else_()),
+                _ => false,
+            })
+}
+
+// Stack allocations in outlined branches are freed on returns from their
+// functions.
+fn has_escaping_stack_allocation(block: &Block) -> bool {
+    let mut names = vec![];
+
+    collect_stack_allocations(block, &mut names);
+
+    if names.is_empty() {
+        return false;
+    }
+
+    let definition =
+        FunctionDefinition::new("", vec![], void_type(), block.clone(), Default::default());
+    let non_escaping_names = alias::collect_non_escaping_allocations(&definition);
+
+    names
+        .into_iter()
+        .any(|name| !non_escaping_names.contains(name))
+}
+
+fn collect_stack_allocations<'a>(block: &'a Block, names: &mut Vec<&'a str>) {
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::AllocateStack(allocate) => names.push(allocate.name()),
+            Instruction::If(if_) => {
+                collect_stack_allocations(if_.then(), names);
+                collect_stack_allocations(if_.else_(), names);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn outline_block(context: &mut Context<impl TargetCostModel>, block: &mut Block, type_: &Type) {
+    let arguments = collect_live_in_variables(block)
+        .into_iter()
+        .filter_map(|name| {
+            context
+                .variables
+                .get(name)
+                .map(|type_| Argument::new(name, type_.clone()))
+        })
+        .collect::<Vec<_>>();
+    let name = global_name::generate(&format!("{}_outlined", context.name), context.names);
+    let mut result_name = format!("{}_result", name);
+
+    while context.variables.contains_key(&result_name) {
+        result_name.push('_');
+    }
+
+    let mut body = replace(
+        block,
+        Block::new(
+            vec![],
+            Branch::new(type_.clone(), Variable::new(&result_name)),
+        ),
+    );
+
+    if let TerminalInstruction::Branch(branch) = body.terminal_instruction() {
+        *body.terminal_instruction_mut() =
+            Return::new(branch.type_().clone(), branch.expression().clone()).into();
+    }
+
+    let definition = FunctionDefinition::new(
+        &name,
+        arguments.clone(),
+        type_.clone(),
+        body,
+        FunctionDefinitionOptions::new().set_linkage(Linkage::Internal),
+    );
+
+    block.instructions_mut().push(
+        Call::new(
+            definition.type_(),
+            Variable::new(name),
+            arguments
+                .iter()
+                .map(|argument| Variable::new(argument.name()).into())
+                .collect(),
+            result_name,
+        )
+        .into(),
+    );
+
+    context.definitions.push(definition);
+}
+
+// Returns names of variables used but not defined in a block in order of their
+// first uses.
+fn collect_live_in_variables(block: &Block) -> Vec<&str> {
+    let mut used_names = vec![];
+    let mut defined_names = FnvHashSet::default();
+
+    collect_from_block(block, &mut used_names, &mut defined_names);
+
+    let mut names = FnvHashSet::default();
+
+    used_names
+        .into_iter()
+        .filter(|name| !defined_names.contains(name) && names.insert(*name))
+        .collect()
+}
+
+fn collect_from_block<'a>(
+    block: &'a Block,
+    used_names: &mut Vec<&'a str>,
+    defined_names: &mut FnvHashSet<&'a str>,
+) {
+    for instruction in block.instructions() {
+        for operand in instruction.operands() {
+            used_names.extend(operand.referenced_variables());
+        }
+
+        if let Instruction::If(if_) = instruction {
+            collect_from_block(if_.then(), used_names, defined_names);
+            collect_from_block(if_.else_(), used_names, defined_names);
+        }
+
+        if let Some((name, _)) = instruction.value() {
+            defined_names.insert(name);
+        }
+    }
+
+    match block.terminal_instruction() {
+        TerminalInstruction::Branch(branch) => {
+            used_names.extend(branch.expression().referenced_variables())
+        }
+        TerminalInstruction::Return(return_) => {
+            used_names.extend(return_.expression().referenced_variables())
+        }
+        TerminalInstruction::Unreachable => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::{cost::GenericCostModel, name, type_check},
+        types,
+    };
+    use pretty_assertions::assert_eq;
+
+    const THRESHOLD: u32 = 4;
+
+    fn create_load(pointer: &str, name: &str) -> Instruction {
+        Load::new(
+            types::Primitive::PointerInteger,
+            Variable::new(pointer),
+            name,
+        )
+        .into()
+    }
+
+    fn create_sum(lhs: &str, rhs: &str) -> Expression {
+        ArithmeticOperation::new(
+            types::Primitive::PointerInteger,
+            ArithmeticOperator::Add,
+            Variable::new(lhs),
+            Variable::new(rhs),
+        )
+        .into()
+    }
+
+    fn create_module(else_: Block) -> Module {
+        let pointer_type = types::Pointer::new(types::Primitive::PointerInteger);
+
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![
+                    Argument::new("p", pointer_type.clone()),
+                    Argument::new("q", pointer_type),
+                    Argument::new("c", types::Primitive::Boolean),
+                ],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    vec![
+                        create_load("p", "x"),
+                        If::new(
+                            types::Primitive::PointerInteger,
+                            Variable::new("c"),
+                            Block::new(
+                                vec![],
+                                Branch::new(types::Primitive::PointerInteger, Variable::new("x")),
+                            ),
+                            else_,
+                            "y",
+                        )
+                        .into(),
+                    ],
+                    Return::new(types::Primitive::PointerInteger, Variable::new("y")),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module, &GenericCostModel, THRESHOLD);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn outline_large_else_branch() {
+        let module = transform_module(create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                create_load("p", "d"),
+            ],
+            Branch::new(types::Primitive::PointerInteger, create_sum("a", "x")),
+        )));
+
+        let definition = &module.function_definitions()[1];
+
+        assert_eq!(definition.name(), "f_outlined_0");
+        assert_eq!(
+            definition.arguments(),
+            &[
+                Argument::new("q", types::Pointer::new(types::Primitive::PointerInteger)),
+                Argument::new("p", types::Pointer::new(types::Primitive::PointerInteger)),
+                Argument::new("x", types::Primitive::PointerInteger),
+            ]
+        );
+        assert_eq!(definition.options().linkage(), Linkage::Internal);
+
+        if let Instruction::If(if_) = &module.function_definitions()[0].body().instructions()[1] {
+            assert_eq!(
+                if_.else_(),
+                &Block::new(
+                    vec![Call::new(
+                        definition.type_(),
+                        Variable::new("f_outlined_0"),
+                        vec![
+                            Variable::new("q").into(),
+                            Variable::new("p").into(),
+                            Variable::new("x").into(),
+                        ],
+                        "f_outlined_0_result",
+                    )
+                    .into()],
+                    Branch::new(
+                        types::Primitive::PointerInteger,
+                        Variable::new("f_outlined_0_result")
+                    ),
+                )
+            );
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn keep_small_branch() {
+        let module = create_module(Block::new(
+            vec![create_load("q", "a")],
+            Branch::new(types::Primitive::PointerInteger, Variable::new("a")),
+        ));
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_branch_cheap_in_cost_model() {
+        struct FreeLoadCostModel;
+
+        impl TargetCostModel for FreeLoadCostModel {
+            fn instruction_cost(&self, instruction: &Instruction) -> u32 {
+                match instruction {
+                    Instruction::Load(_) => 0,
+                    _ => GenericCostModel.instruction_cost(instruction),
+                }
+            }
+        }
+
+        let module = create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                create_load("p", "d"),
+            ],
+            Branch::new(types::Primitive::PointerInteger, create_sum("a", "x")),
+        ));
+        let mut transformed_module = module.clone();
+
+        transform(&mut transformed_module, &FreeLoadCostModel, THRESHOLD);
+
+        assert_eq!(transformed_module, module);
+    }
+
+    #[test]
+    fn keep_large_branch_with_return() {
+        let module = create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                create_load("p", "d"),
+            ],
+            Return::new(types::Primitive::PointerInteger, Variable::new("a")),
+        ));
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_large_branch_with_nested_return() {
+        let module = create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                If::new(
+                    types::Primitive::PointerInteger,
+                    Variable::new("c"),
+                    Block::new(
+                        vec![],
+                        Return::new(types::Primitive::PointerInteger, Variable::new("a")),
+                    ),
+                    Block::new(
+                        vec![],
+                        Branch::new(types::Primitive::PointerInteger, Variable::new("b")),
+                    ),
+                    "d",
+                )
+                .into(),
+            ],
+            Branch::new(types::Primitive::PointerInteger, Variable::new("d")),
+        ));
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_large_branch_returning_own_stack_pointer() {
+        let pointer_type = types::Pointer::new(types::Primitive::PointerInteger);
+        let module = create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                AllocateStack::new(types::Primitive::PointerInteger, "s").into(),
+                Store::new(
+                    types::Primitive::PointerInteger,
+                    Variable::new("a"),
+                    Variable::new("s"),
+                )
+                .into(),
+            ],
+            Branch::new(
+                types::Primitive::PointerInteger,
+                BitCast::new(
+                    pointer_type,
+                    types::Primitive::PointerInteger,
+                    Variable::new("s"),
+                ),
+            ),
+        ));
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_large_branch_storing_own_stack_pointer() {
+        let pointer_type = types::Pointer::new(types::Primitive::PointerInteger);
+        let module = create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                AllocateStack::new(types::Primitive::PointerInteger, "s").into(),
+                Store::new(
+                    pointer_type.clone(),
+                    Variable::new("s"),
+                    BitCast::new(
+                        pointer_type.clone(),
+                        types::Pointer::new(pointer_type),
+                        Variable::new("q"),
+                    ),
+                )
+                .into(),
+            ],
+            Branch::new(types::Primitive::PointerInteger, Variable::new("a")),
+        ));
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn outline_large_branch_with_local_stack_allocation() {
+        let module = transform_module(create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                AllocateStack::new(types::Primitive::PointerInteger, "s").into(),
+                Store::new(
+                    types::Primitive::PointerInteger,
+                    Variable::new("a"),
+                    Variable::new("s"),
+                )
+                .into(),
+                create_load("s", "b"),
+            ],
+            Branch::new(types::Primitive::PointerInteger, Variable::new("b")),
+        )));
+
+        assert_eq!(module.function_definitions().len(), 2);
+    }
+
+    #[test]
+    fn generate_name_different_from_existing_global_names() {
+        let mut module = create_module(Block::new(
+            vec![
+                create_load("q", "a"),
+                create_load("q", "b"),
+                create_load("p", "d"),
+            ],
+            Branch::new(types::Primitive::PointerInteger, create_sum("a", "x")),
+        ));
+        module
+            .function_declarations_mut()
+            .push(FunctionDeclaration::new(
+                "f_outlined_0",
+                types::Function::new(
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    types::CallingConvention::Source,
+                ),
+            ));
+
+        let module = transform_module(module);
+
+        name::check(&module).unwrap();
+        assert_eq!(module.function_definitions()[1].name(), "f_outlined_1");
+    }
+}
@@ -0,0 +1,313 @@
+mod error;
+
+use crate::ir::*;
+pub use error::*;
+#[cfg(feature = "timing")]
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+type Pass = Box<dyn Fn(&Module) -> Module>;
+
+const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+pub struct PassManager {
+    passes: Vec<(String, Pass)>,
+    fixed_point: bool,
+    max_iterations: Option<usize>,
+    #[cfg(feature = "timing")]
+    timings: RefCell<Vec<(String, Duration)>>,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self {
+            passes: vec![],
+            fixed_point: false,
+            max_iterations: Some(DEFAULT_MAX_ITERATIONS),
+            #[cfg(feature = "timing")]
+            timings: Default::default(),
+        }
+    }
+
+    pub fn add_pass(
+        mut self,
+        name: impl Into<String>,
+        pass: impl Fn(&Module) -> Module + 'static,
+    ) -> Self {
+        self.passes.push((name.into(), Box::new(pass)));
+        self
+    }
+
+    pub fn set_fixed_point(self, fixed_point: bool) -> Self {
+        Self {
+            fixed_point,
+            ..self
+        }
+    }
+
+    // Limits the number of iterations to reach fixed points. Iterations are
+    // unlimited if `None` is given.
+    pub fn set_max_iterations(self, max_iterations: Option<usize>) -> Self {
+        Self {
+            max_iterations,
+            ..self
+        }
+    }
+
+    pub fn pass_names(&self) -> impl Iterator<Item = &str> {
+        self.passes.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn run(&self, module: &Module) -> Result<Module, PassManagerError> {
+        let mut module = module.clone();
+        let mut iterations = 0;
+
+        #[cfg(feature = "timing")]
+        self.timings.borrow_mut().clear();
+
+        loop {
+            let (other, changed_names) = self.run_once(&module);
+            iterations += 1;
+
+            if !self.fixed_point || changed_names.is_empty() {
+                return Ok(other);
+            } else if matches!(self.max_iterations, Some(max) if iterations >= max) {
+                return Err(PassManagerError::FixedPointNotReached(changed_names));
+            }
+
+            module = other;
+        }
+    }
+
+    // Changes of modules are tracked only in fixed point mode.
+    fn run_once(&self, module: &Module) -> (Module, Vec<String>) {
+        let mut module = module.clone();
+        let mut changed_names = vec![];
+
+        for (name, pass) in &self.passes {
+            #[cfg(feature = "timing")]
+            let time = Instant::now();
+
+            let other = pass(&module);
+
+            #[cfg(feature = "timing")]
+            self.timings
+                .borrow_mut()
+                .push((name.clone(), time.elapsed()));
+
+            if self.fixed_point && other != module {
+                changed_names.push(name.clone());
+            }
+
+            module = other;
+        }
+
+        (module, changed_names)
+    }
+
+    #[cfg(feature = "timing")]
+    pub fn timings(&self) -> Vec<(String, Duration)> {
+        self.timings.borrow().clone()
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+    use pretty_assertions::assert_eq;
+    use std::{cell::Cell, rc::Rc};
+
+    fn create_module(value: i64) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![VariableDefinition::new(
+                "x",
+                Primitive::PointerInteger(value),
+                types::Primitive::PointerInteger,
+                Default::default(),
+            )],
+            vec![],
+        )
+    }
+
+    fn decrement(module: &Module) -> Module {
+        let mut module = module.clone();
+
+        for definition in module.variable_definitions_mut() {
+            if let Expression::Primitive(Primitive::PointerInteger(value)) = definition.body() {
+                *definition.body_mut() = Primitive::PointerInteger((value - 1).max(0)).into();
+            }
+        }
+
+        module
+    }
+
+    #[test]
+    fn run_nothing() {
+        assert_eq!(
+            PassManager::new().run(&create_module(42)),
+            Ok(create_module(42))
+        );
+    }
+
+    #[test]
+    fn run_passes_in_order() {
+        let manager = PassManager::new()
+            .add_pass("decrement", decrement)
+            .add_pass("double", |module| {
+                let mut module = module.clone();
+
+                for definition in module.variable_definitions_mut() {
+                    if let Expression::Primitive(Primitive::PointerInteger(value)) =
+                        definition.body()
+                    {
+                        *definition.body_mut() = Primitive::PointerInteger(value * 2).into();
+                    }
+                }
+
+                module
+            });
+
+        assert_eq!(
+            manager.pass_names().collect::<Vec<_>>(),
+            vec!["decrement", "double"]
+        );
+        assert_eq!(manager.run(&create_module(3)), Ok(create_module(4)));
+    }
+
+    #[test]
+    fn run_passes_to_fixed_point() {
+        let manager = PassManager::new()
+            .add_pass("decrement", decrement)
+            .add_pass("decrement_again", decrement)
+            .set_fixed_point(true);
+
+        assert_eq!(manager.run(&create_module(5)), Ok(create_module(0)));
+    }
+
+    #[test]
+    fn converge_within_max_iterations() {
+        let manager = PassManager::new()
+            .add_pass("decrement", decrement)
+            .set_fixed_point(true)
+            .set_max_iterations(Some(2));
+
+        assert_eq!(manager.run(&create_module(1)), Ok(create_module(0)));
+    }
+
+    #[test]
+    fn run_passes_at_most_max_iterations() {
+        let count = Rc::new(Cell::new(0));
+        let manager = PassManager::new()
+            .add_pass("increment", {
+                let count = count.clone();
+
+                move |_| {
+                    count.set(count.get() + 1);
+                    create_module(count.get())
+                }
+            })
+            .set_fixed_point(true)
+            .set_max_iterations(Some(3));
+
+        assert_eq!(
+            manager.run(&create_module(0)),
+            Err(PassManagerError::FixedPointNotReached(vec![
+                "increment".into()
+            ]))
+        );
+        assert_eq!(count.get(), 3);
+    }
+
+    #[test]
+    fn fail_to_reach_fixed_point() {
+        let manager = PassManager::new()
+            .add_pass("decrement", decrement)
+            .add_pass("increment", |module| {
+                let mut module = module.clone();
+
+                for definition in module.variable_definitions_mut() {
+                    if let Expression::Primitive(Primitive::PointerInteger(value)) =
+                        definition.body()
+                    {
+                        *definition.body_mut() = Primitive::PointerInteger(value + 1).into();
+                    }
+                }
+
+                module
+            })
+            .set_fixed_point(true)
+            .set_max_iterations(Some(10));
+
+        assert_eq!(
+            manager.run(&create_module(1)),
+            Err(PassManagerError::FixedPointNotReached(vec![
+                "decrement".into(),
+                "increment".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn fail_to_reach_fixed_point_within_default_max_iterations() {
+        let count = Rc::new(Cell::new(0));
+        let manager = PassManager::new()
+            .add_pass("increment", {
+                let count = count.clone();
+
+                move |_| {
+                    count.set(count.get() + 1);
+                    create_module(count.get())
+                }
+            })
+            .set_fixed_point(true);
+
+        assert_eq!(
+            manager.run(&create_module(0)),
+            Err(PassManagerError::FixedPointNotReached(vec![
+                "increment".into()
+            ]))
+        );
+        assert_eq!(count.get(), DEFAULT_MAX_ITERATIONS as i64);
+    }
+
+    #[cfg(feature = "timing")]
+    #[test]
+    fn record_timings() {
+        let manager = PassManager::new()
+            .add_pass("decrement", decrement)
+            .set_fixed_point(true);
+
+        manager.run(&create_module(2)).unwrap();
+
+        assert_eq!(
+            manager
+                .timings()
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["decrement"; 3]
+        );
+    }
+
+    #[cfg(feature = "timing")]
+    #[test]
+    fn record_timings_of_last_run() {
+        let manager = PassManager::new().add_pass("decrement", decrement);
+
+        manager.run(&create_module(2)).unwrap();
+        manager.run(&create_module(2)).unwrap();
+
+        assert_eq!(manager.timings().len(), 1);
+    }
+}
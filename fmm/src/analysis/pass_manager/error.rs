@@ -0,0 +1,18 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PassManagerError {
+    // Names of passes which changed modules in the last iteration.
+    FixedPointNotReached(Vec<String>),
+}
+
+impl Display for PassManagerError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}", self)
+    }
+}
+
+impl Error for PassManagerError {}
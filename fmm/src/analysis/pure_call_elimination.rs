@@ -0,0 +1,218 @@
+use super::expression_conversion;
+use crate::ir::*;
+use fnv::{FnvHashMap, FnvHashSet};
+use std::mem::take;
+
+// Remove calls of pure functions with the same arguments as preceding ones in
+// the same or outer blocks. Their results are replaced with the preceding ones.
+pub fn transform(module: &mut Module) {
+    let names = module
+        .function_definitions()
+        .iter()
+        .filter(|definition| definition.options().is_pure())
+        .map(|definition| definition.name().to_owned())
+        .collect::<FnvHashSet<_>>();
+
+    if names.is_empty() {
+        return;
+    }
+
+    for definition in module.function_definitions_mut() {
+        transform_block(
+            definition.body_mut(),
+            &names,
+            &mut vec![],
+            &mut Default::default(),
+        );
+    }
+}
+
+fn transform_block(
+    block: &mut Block,
+    names: &FnvHashSet<String>,
+    calls: &mut Vec<Call>,
+    values: &mut FnvHashMap<String, Expression>,
+) {
+    let mut instructions = Vec::with_capacity(block.instructions().len());
+
+    for mut instruction in take(block.instructions_mut()) {
+        expression_conversion::convert_in_instruction(&mut instruction, &|expression| {
+            replace_value(expression, values)
+        });
+
+        match &mut instruction {
+            Instruction::Call(call) if is_pure_call(call, names) => {
+                if let Some(other) = calls.iter().find(|other| is_same_call(call, other)) {
+                    values.insert(call.name().into(), Variable::new(other.name()).into());
+                    continue;
+                }
+
+                calls.push(call.clone());
+            }
+            Instruction::If(if_) => {
+                let length = calls.len();
+
+                transform_block(if_.then_mut(), names, calls, values);
+                calls.truncate(length);
+                transform_block(if_.else_mut(), names, calls, values);
+                calls.truncate(length);
+            }
+            _ => {}
+        }
+
+        instructions.push(instruction);
+    }
+
+    *block.instructions_mut() = instructions;
+
+    expression_conversion::convert_in_terminal_instruction(
+        block.terminal_instruction_mut(),
+        &|expression| replace_value(expression, values),
+    );
+}
+
+fn is_pure_call(call: &Call, names: &FnvHashSet<String>) -> bool {
+    matches!(call.function(), Expression::Variable(variable) if names.contains(variable.name()))
+}
+
+fn is_same_call(one: &Call, other: &Call) -> bool {
+    one.type_() == other.type_()
+        && one.function() == other.function()
+        && one.arguments() == other.arguments()
+}
+
+fn replace_value(expression: &Expression, values: &FnvHashMap<String, Expression>) -> Expression {
+    match expression {
+        Expression::Variable(variable) => values
+            .get(variable.name())
+            .cloned()
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::type_check, types};
+    use pretty_assertions::assert_eq;
+
+    fn create_callee(name: &str, pure: bool) -> FunctionDefinition {
+        FunctionDefinition::new(
+            name,
+            vec![Argument::new("x", types::Primitive::PointerInteger)],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![],
+                Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+            ),
+            FunctionDefinitionOptions::new().set_pure(pure),
+        )
+    }
+
+    fn create_call(function: &str, argument: i64, name: &str) -> Instruction {
+        Call::new(
+            create_callee(function, false).type_(),
+            Variable::new(function),
+            vec![Primitive::PointerInteger(argument).into()],
+            name,
+        )
+        .into()
+    }
+
+    fn create_module(instructions: Vec<Instruction>, result: impl Into<Expression>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_callee("f", true),
+                create_callee("g", false),
+                FunctionDefinition::new(
+                    "h",
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    Block::new(
+                        instructions,
+                        Return::new(types::Primitive::PointerInteger, result),
+                    ),
+                    Default::default(),
+                ),
+            ],
+        )
+    }
+
+    fn create_sum(lhs: &str, rhs: &str) -> Expression {
+        ArithmeticOperation::new(
+            types::Primitive::PointerInteger,
+            ArithmeticOperator::Add,
+            Variable::new(lhs),
+            Variable::new(rhs),
+        )
+        .into()
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn remove_duplicate_pure_call() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![create_call("f", 42, "x"), create_call("f", 42, "y")],
+                create_sum("x", "y"),
+            )),
+            create_module(vec![create_call("f", 42, "x")], create_sum("x", "x"))
+        );
+    }
+
+    #[test]
+    fn keep_pure_calls_with_different_arguments() {
+        let module = create_module(
+            vec![create_call("f", 1, "x"), create_call("f", 2, "y")],
+            create_sum("x", "y"),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_impure_calls() {
+        let module = create_module(
+            vec![create_call("g", 42, "x"), create_call("g", 42, "y")],
+            create_sum("x", "y"),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_pure_calls_in_different_branches() {
+        let create_block = |name| {
+            Block::new(
+                vec![create_call("f", 42, name)],
+                Branch::new(types::Primitive::PointerInteger, Variable::new(name)),
+            )
+        };
+        let module = create_module(
+            vec![If::new(
+                types::Primitive::PointerInteger,
+                Primitive::Boolean(true),
+                create_block("x"),
+                create_block("y"),
+                "z",
+            )
+            .into()],
+            Variable::new("z"),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
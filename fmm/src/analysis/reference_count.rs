@@ -0,0 +1,343 @@
+use crate::{
+    build::NameGenerator,
+    ir::*,
+    types::{self, Type},
+};
+use std::mem::take;
+
+struct Context<'a> {
+    retain: &'a FunctionDeclaration,
+    release: &'a FunctionDeclaration,
+    is_reference_counted: &'a dyn Fn(&Type) -> bool,
+    name_generator: NameGenerator,
+}
+
+// Insert calls of retain and release functions for values of reference
+// counted types.
+//
+// Functions own their arguments and results of calls and if instructions.
+// Owned values are released when they go out of scope unless they are
+// returned or branched. Values are retained when they are stored, passed to
+// calls, or returned or branched without ownership.
+pub fn transform(
+    module: &mut Module,
+    retain: &FunctionDeclaration,
+    release: &FunctionDeclaration,
+    is_reference_counted: impl Fn(&Type) -> bool,
+) {
+    let mut context = Context {
+        retain,
+        release,
+        is_reference_counted: &is_reference_counted,
+        name_generator: NameGenerator::new("_rc_"),
+    };
+
+    for definition in module.function_definitions_mut() {
+        let mut scope = definition
+            .arguments()
+            .iter()
+            .filter(|argument| is_reference_counted(argument.type_()))
+            .map(|argument| (argument.name().to_owned(), argument.type_().clone()))
+            .collect();
+
+        transform_block(&mut context, definition.body_mut(), &mut scope);
+    }
+
+    for declaration in [retain, release] {
+        if !module
+            .function_declarations()
+            .iter()
+            .any(|other| other.name() == declaration.name())
+            && !module
+                .function_definitions()
+                .iter()
+                .any(|definition| definition.name() == declaration.name())
+        {
+            module.function_declarations_mut().push(declaration.clone());
+        }
+    }
+}
+
+fn transform_block(context: &mut Context, block: &mut Block, scope: &mut Vec<(String, Type)>) {
+    let base = scope.len();
+    let mut instructions = Vec::with_capacity(block.instructions().len());
+
+    for mut instruction in take(block.instructions_mut()) {
+        match &mut instruction {
+            Instruction::Call(call) => {
+                for (argument, type_) in call.arguments().iter().zip(call.type_().arguments()) {
+                    if (context.is_reference_counted)(type_) {
+                        instructions.push(call_function(context, true, argument, type_));
+                    }
+                }
+            }
+            Instruction::If(if_) => {
+                transform_block(context, if_.then_mut(), scope);
+                transform_block(context, if_.else_mut(), scope);
+            }
+            Instruction::Store(store) if (context.is_reference_counted)(store.type_()) => {
+                instructions.push(call_function(context, true, store.value(), store.type_()));
+            }
+            _ => {}
+        }
+
+        if let (Instruction::Call(_) | Instruction::If(_), Some((name, type_))) =
+            (&instruction, instruction.value())
+        {
+            if (context.is_reference_counted)(&type_) {
+                scope.push((name.into(), type_));
+            }
+        }
+
+        instructions.push(instruction);
+    }
+
+    let (expression, type_, released_scope) = match block.terminal_instruction() {
+        TerminalInstruction::Branch(branch) => {
+            (Some(branch.expression()), branch.type_(), &scope[base..])
+        }
+        TerminalInstruction::Return(return_) => {
+            (Some(return_.expression()), return_.type_(), &scope[..])
+        }
+        TerminalInstruction::Unreachable => (None, &Type::from(types::void_type()), &[][..]),
+    };
+
+    // Only values released at the end of the block can be moved. The others
+    // are owned by outer blocks.
+    let moved_name = match expression {
+        Some(Expression::Variable(variable))
+            if released_scope
+                .iter()
+                .any(|(name, _)| name == variable.name()) =>
+        {
+            Some(variable.name())
+        }
+        _ => None,
+    };
+
+    if let Some(expression) = expression {
+        if moved_name.is_none() && (context.is_reference_counted)(type_) {
+            instructions.push(call_function(context, true, expression, type_));
+        }
+    }
+
+    let released = released_scope
+        .iter()
+        .filter(|(name, _)| Some(name.as_str()) != moved_name)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for (name, type_) in released {
+        instructions.push(call_function(
+            context,
+            false,
+            &Variable::new(name).into(),
+            &type_,
+        ));
+    }
+
+    *block.instructions_mut() = instructions;
+
+    scope.truncate(base);
+}
+
+fn call_function(
+    context: &mut Context,
+    retain: bool,
+    value: &Expression,
+    type_: &Type,
+) -> Instruction {
+    let declaration = if retain {
+        context.retain
+    } else {
+        context.release
+    };
+    let argument_type = declaration
+        .type_()
+        .arguments()
+        .first()
+        .cloned()
+        .unwrap_or_else(|| type_.clone());
+
+    Call::new(
+        declaration.type_().clone(),
+        Variable::new(declaration.name()),
+        vec![if &argument_type == type_ {
+            value.clone()
+        } else {
+            BitCast::new(type_.clone(), argument_type, value.clone()).into()
+        }],
+        context.name_generator.generate(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::validation,
+        types::{generic_pointer_type, void_type},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn reference_type() -> types::Pointer {
+        types::Pointer::new(types::Primitive::Integer64)
+    }
+
+    fn create_declaration(name: &str) -> FunctionDeclaration {
+        FunctionDeclaration::new(
+            name,
+            types::Function::new(
+                vec![generic_pointer_type()],
+                void_type(),
+                types::CallingConvention::Target,
+            ),
+        )
+    }
+
+    fn create_call(function: &str, name: &str, argument: &str) -> Instruction {
+        Call::new(
+            create_declaration(function).type_().clone(),
+            Variable::new(function),
+            vec![BitCast::new(
+                reference_type(),
+                generic_pointer_type(),
+                Variable::new(argument),
+            )
+            .into()],
+            name,
+        )
+        .into()
+    }
+
+    fn create_module(result_type: impl Into<Type>, body: Block) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new("x", reference_type())],
+                result_type,
+                body,
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        transform(
+            &mut module,
+            &create_declaration("retain"),
+            &create_declaration("release"),
+            |type_| type_ == &reference_type().into(),
+        );
+
+        validation::validate(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn release_argument_at_end_of_function() {
+        let module = transform_module(create_module(
+            void_type(),
+            Block::new(vec![], Return::new(void_type(), void_value())),
+        ));
+
+        assert_eq!(
+            module.function_declarations(),
+            &[create_declaration("retain"), create_declaration("release")]
+        );
+        assert_eq!(
+            module.function_definitions()[0].body(),
+            &Block::new(
+                vec![create_call("release", "_rc_0", "x")],
+                Return::new(void_type(), void_value())
+            )
+        );
+    }
+
+    #[test]
+    fn move_returned_argument() {
+        let block = Block::new(vec![], Return::new(reference_type(), Variable::new("x")));
+
+        assert_eq!(
+            transform_module(create_module(reference_type(), block.clone())).function_definitions()
+                [0]
+            .body(),
+            &block
+        );
+    }
+
+    #[test]
+    fn retain_stored_argument() {
+        assert_eq!(
+            transform_module(create_module(
+                void_type(),
+                Block::new(
+                    vec![
+                        AllocateStack::new(reference_type(), "p").into(),
+                        Store::new(reference_type(), Variable::new("x"), Variable::new("p")).into(),
+                    ],
+                    Return::new(void_type(), void_value())
+                ),
+            ))
+            .function_definitions()[0]
+                .body(),
+            &Block::new(
+                vec![
+                    AllocateStack::new(reference_type(), "p").into(),
+                    create_call("retain", "_rc_0", "x"),
+                    Store::new(reference_type(), Variable::new("x"), Variable::new("p")).into(),
+                    create_call("release", "_rc_1", "x"),
+                ],
+                Return::new(void_type(), void_value())
+            )
+        );
+    }
+
+    #[test]
+    fn retain_argument_branched_out_of_if() {
+        let create_branch = |name| {
+            Block::new(
+                vec![create_call("retain", name, "x")],
+                Branch::new(reference_type(), Variable::new("x")),
+            )
+        };
+
+        assert_eq!(
+            transform_module(create_module(
+                reference_type(),
+                Block::new(
+                    vec![If::new(
+                        reference_type(),
+                        Primitive::Boolean(true),
+                        Block::new(vec![], Branch::new(reference_type(), Variable::new("x"))),
+                        Block::new(vec![], Branch::new(reference_type(), Variable::new("x"))),
+                        "y",
+                    )
+                    .into()],
+                    Return::new(reference_type(), Variable::new("y"))
+                ),
+            ))
+            .function_definitions()[0]
+                .body(),
+            &Block::new(
+                vec![
+                    If::new(
+                        reference_type(),
+                        Primitive::Boolean(true),
+                        create_branch("_rc_0"),
+                        create_branch("_rc_1"),
+                        "y",
+                    )
+                    .into(),
+                    create_call("release", "_rc_2", "x"),
+                ],
+                Return::new(reference_type(), Variable::new("y"))
+            )
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use crate::ir::*;
+use super::local_variable;
+use crate::{build::NameGenerator, ir::*};
+use fnv::{FnvHashMap, FnvHashSet};
 
 pub fn rename(module: &Module, rename: impl Fn(&str) -> String) -> Module {
     Module::new(
@@ -25,6 +27,56 @@ pub fn rename(module: &Module, rename: impl Fn(&str) -> String) -> Module {
     )
 }
 
+// Prefixes names of definitions with internal linkage.
+pub fn mangle_symbols(module: &Module, prefix: &str) -> Module {
+    let names = module
+        .variable_definitions()
+        .iter()
+        .filter(|definition| definition.options().linkage() == Linkage::Internal)
+        .map(|definition| definition.name())
+        .chain(
+            module
+                .function_definitions()
+                .iter()
+                .filter(|definition| definition.options().linkage() == Linkage::Internal)
+                .map(|definition| definition.name()),
+        )
+        .collect::<FnvHashSet<_>>();
+
+    rename(module, |name| {
+        if names.contains(name) {
+            format!("{}{}", prefix, name)
+        } else {
+            name.into()
+        }
+    })
+}
+
+// Clones a function definition renaming its local variables with a fresh
+// prefix so that both definitions can live in the same module.
+pub fn clone_function(
+    definition: &FunctionDefinition,
+    name: &str,
+    name_generator: &mut NameGenerator,
+) -> FunctionDefinition {
+    let prefix = name_generator.generate();
+    let arguments = definition
+        .arguments()
+        .iter()
+        .map(|argument| argument.name())
+        .collect::<Vec<_>>();
+    let names = local_variable::collect(definition)
+        .into_keys()
+        .filter(|name| !arguments.contains(name))
+        .map(|local_name| (local_name, format!("{}_{}", prefix, local_name)))
+        .chain([(definition.name(), name.into())])
+        .collect::<FnvHashMap<_, _>>();
+
+    rename_function_definition(definition, &|name| {
+        names.get(name).cloned().unwrap_or_else(|| name.into())
+    })
+}
+
 fn rename_variable_declaration(
     declaration: &VariableDeclaration,
     rename: &impl Fn(&str) -> String,
@@ -51,7 +103,7 @@ fn rename_variable_definition(
     )
 }
 
-fn rename_function_definition(
+pub fn rename_function_definition(
     definition: &FunctionDefinition,
     rename: &impl Fn(&str) -> String,
 ) -> FunctionDefinition {
@@ -60,7 +112,13 @@ fn rename_function_definition(
         definition
             .arguments()
             .iter()
-            .map(|argument| Argument::new(rename(argument.name()), argument.type_().clone()))
+            .map(|argument| {
+                Argument::with_options(
+                    rename(argument.name()),
+                    argument.type_().clone(),
+                    argument.options().clone(),
+                )
+            })
             .collect(),
         definition.result_type().clone(),
         rename_block(definition.body(), rename),
@@ -89,9 +147,15 @@ pub fn rename_instruction(
         Instruction::AllocateHeap(allocate) => {
             AllocateHeap::new(rename_expression(allocate.size()), rename(allocate.name())).into()
         }
-        Instruction::AllocateStack(allocate) => {
-            AllocateStack::new(allocate.type_().clone(), rename(allocate.name())).into()
+        Instruction::AllocateStack(allocate) => match allocate.count() {
+            Some(count) => AllocateStack::array(
+                allocate.type_().clone(),
+                rename_expression(count),
+                rename(allocate.name()),
+            ),
+            None => AllocateStack::new(allocate.type_().clone(), rename(allocate.name())),
         }
+        .into(),
         Instruction::AtomicLoad(load) => AtomicLoad::new(
             load.type_().clone(),
             rename_expression(load.pointer()),
@@ -99,14 +163,24 @@ pub fn rename_instruction(
             rename(load.name()),
         )
         .into(),
-        Instruction::AtomicOperation(operation) => AtomicOperation::new(
-            operation.type_(),
-            operation.operator(),
-            rename_expression(operation.pointer()),
-            rename_expression(operation.value()),
-            operation.ordering(),
-            rename(operation.name()),
-        )
+        Instruction::AtomicOperation(operation) => if operation.is_result_discarded() {
+            AtomicOperation::without_result(
+                operation.type_(),
+                operation.operator(),
+                rename_expression(operation.pointer()),
+                rename_expression(operation.value()),
+                operation.ordering(),
+            )
+        } else {
+            AtomicOperation::new(
+                operation.type_(),
+                operation.operator(),
+                rename_expression(operation.pointer()),
+                rename_expression(operation.value()),
+                operation.ordering(),
+                rename(operation.name()),
+            )
+        }
         .into(),
         Instruction::AtomicStore(store) => AtomicStore::new(
             store.type_().clone(),
@@ -121,6 +195,7 @@ pub fn rename_instruction(
             call.arguments().iter().map(rename_expression).collect(),
             rename(call.name()),
         )
+        .set_tail_call(call.tail_call())
         .into(),
         Instruction::CompareAndSwap(cas) => CompareAndSwap::new(
             cas.type_().clone(),
@@ -147,7 +222,12 @@ pub fn rename_instruction(
         )
         .into(),
         Instruction::Fence(fence) => fence.clone().into(),
-        Instruction::FreeHeap(free) => FreeHeap::new(rename_expression(free.pointer())).into(),
+        Instruction::FreeHeap(free) => if let Some(type_) = free.type_() {
+            FreeHeap::new_sized(type_.clone(), rename_expression(free.pointer()))
+        } else {
+            FreeHeap::new(rename_expression(free.pointer()))
+        }
+        .into(),
         Instruction::If(if_) => If::new(
             if_.type_().clone(),
             rename_expression(if_.condition()),
@@ -155,12 +235,15 @@ pub fn rename_instruction(
             rename_block(if_.else_(), rename),
             rename(if_.name()),
         )
+        .set_likely(if_.likely())
         .into(),
         Instruction::Load(load) => Load::new(
             load.type_().clone(),
             rename_expression(load.pointer()),
             rename(load.name()),
         )
+        .set_alignment(load.alignment())
+        .set_dereferenceable_bytes(load.dereferenceable_bytes())
         .into(),
         Instruction::MemoryCopy(copy) => MemoryCopy::new(
             rename_expression(copy.source()),
@@ -529,4 +612,123 @@ mod tests {
             )
         );
     }
+
+    mod clone_function {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn clone_function_with_local_variables() {
+            let definition = create_function_definition(
+                "f",
+                vec![Argument::new(
+                    "x",
+                    types::Pointer::new(types::Primitive::PointerInteger),
+                )],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    vec![
+                        Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+                    ],
+                    Return::new(types::Primitive::PointerInteger, Variable::new("y")),
+                ),
+            );
+            let mut name_generator = NameGenerator::new("c");
+
+            let one = clone_function(&definition, "g", &mut name_generator);
+            let other = clone_function(&definition, "h", &mut name_generator);
+
+            assert_eq!(
+                one,
+                create_function_definition(
+                    "g",
+                    vec![Argument::new(
+                        "x",
+                        types::Pointer::new(types::Primitive::PointerInteger),
+                    )],
+                    types::Primitive::PointerInteger,
+                    Block::new(
+                        vec![Load::new(
+                            types::Primitive::PointerInteger,
+                            Variable::new("x"),
+                            "c0_y"
+                        )
+                        .into()],
+                        Return::new(types::Primitive::PointerInteger, Variable::new("c0_y")),
+                    ),
+                )
+            );
+            assert_eq!(other.name(), "h");
+            assert_ne!(
+                local_variable::collect(&one).keys().collect::<Vec<_>>(),
+                local_variable::collect(&other).keys().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    mod mangle_symbols {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn mangle_internal_symbols() {
+            let malloc_type = create_function_type(
+                vec![types::Primitive::PointerInteger.into()],
+                types::generic_pointer_type(),
+            );
+            let create_module = |variable_name, function_name| {
+                Module::new(
+                    vec![],
+                    vec![FunctionDeclaration::new("malloc", malloc_type.clone())],
+                    vec![VariableDefinition::new(
+                        variable_name,
+                        Primitive::PointerInteger(42),
+                        types::Primitive::PointerInteger,
+                        VariableDefinitionOptions::new().set_linkage(Linkage::Internal),
+                    )],
+                    vec![
+                        FunctionDefinition::new(
+                            function_name,
+                            vec![],
+                            types::generic_pointer_type(),
+                            Block::new(
+                                vec![Call::new(
+                                    malloc_type.clone(),
+                                    Variable::new("malloc"),
+                                    vec![Primitive::PointerInteger(8).into()],
+                                    "x",
+                                )
+                                .into()],
+                                Return::new(types::generic_pointer_type(), Variable::new("x")),
+                            ),
+                            FunctionDefinitionOptions::new().set_linkage(Linkage::Internal),
+                        ),
+                        create_function_definition(
+                            "g",
+                            vec![],
+                            types::Pointer::new(types::Primitive::PointerInteger),
+                            Block::new(
+                                vec![Call::new(
+                                    create_function_type(vec![], types::generic_pointer_type()),
+                                    Variable::new(function_name),
+                                    vec![],
+                                    "y",
+                                )
+                                .into()],
+                                Return::new(
+                                    types::Pointer::new(types::Primitive::PointerInteger),
+                                    Variable::new(variable_name),
+                                ),
+                            ),
+                        ),
+                    ],
+                )
+            };
+
+            assert_eq!(
+                mangle_symbols(&create_module("v", "f"), "m_"),
+                create_module("m_v", "m_f")
+            );
+        }
+    }
 }
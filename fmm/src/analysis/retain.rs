@@ -0,0 +1,196 @@
+use crate::ir::*;
+use fnv::{FnvHashMap, FnvHashSet};
+
+// Retains root functions and global symbols referenced by them transitively.
+pub fn retain_functions(module: &Module, roots: &[&str]) -> Module {
+    let function_definitions = module
+        .function_definitions()
+        .iter()
+        .map(|definition| (definition.name(), definition))
+        .collect::<FnvHashMap<_, _>>();
+    let variable_definitions = module
+        .variable_definitions()
+        .iter()
+        .map(|definition| (definition.name(), definition))
+        .collect::<FnvHashMap<_, _>>();
+
+    let mut names = FnvHashSet::default();
+    let mut stack = roots.to_vec();
+
+    while let Some(name) = stack.pop() {
+        if !names.insert(name) {
+            continue;
+        }
+
+        if let Some(definition) = function_definitions.get(name) {
+            let mut referenced_names = vec![];
+
+            collect_referenced_names(definition.body(), &mut referenced_names);
+
+            stack.extend(referenced_names);
+        } else if let Some(definition) = variable_definitions.get(name) {
+            stack.extend(definition.referenced_symbols());
+        }
+    }
+
+    Module::new(
+        module
+            .variable_declarations()
+            .iter()
+            .filter(|declaration| names.contains(declaration.name()))
+            .cloned()
+            .collect(),
+        module
+            .function_declarations()
+            .iter()
+            .filter(|declaration| names.contains(declaration.name()))
+            .cloned()
+            .collect(),
+        module
+            .variable_definitions()
+            .iter()
+            .filter(|definition| names.contains(definition.name()))
+            .cloned()
+            .collect(),
+        module
+            .function_definitions()
+            .iter()
+            .filter(|definition| names.contains(definition.name()))
+            .cloned()
+            .collect(),
+    )
+}
+
+// Local variables are collected too but they never match global names.
+pub(crate) fn collect_referenced_names<'a>(block: &'a Block, names: &mut Vec<&'a str>) {
+    for instruction in block.instructions() {
+        for operand in instruction.operands() {
+            names.extend(operand.referenced_variables());
+        }
+
+        if let Instruction::If(if_) = instruction {
+            collect_referenced_names(if_.then(), names);
+            collect_referenced_names(if_.else_(), names);
+        }
+    }
+
+    match block.terminal_instruction() {
+        TerminalInstruction::Branch(branch) => {
+            names.extend(branch.expression().referenced_variables())
+        }
+        TerminalInstruction::Return(return_) => {
+            names.extend(return_.expression().referenced_variables())
+        }
+        TerminalInstruction::Unreachable => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+    use pretty_assertions::assert_eq;
+
+    fn create_function_type() -> types::Function {
+        types::Function::new(
+            vec![],
+            types::Primitive::PointerInteger,
+            types::CallingConvention::Source,
+        )
+    }
+
+    fn create_function(name: &str, callee: Option<&str>) -> FunctionDefinition {
+        FunctionDefinition::new(
+            name,
+            vec![],
+            types::Primitive::PointerInteger,
+            if let Some(callee) = callee {
+                Block::new(
+                    vec![
+                        Call::new(create_function_type(), Variable::new(callee), vec![], "x")
+                            .into(),
+                    ],
+                    Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+                )
+            } else {
+                Block::new(
+                    vec![],
+                    Return::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(42),
+                    ),
+                )
+            },
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn retain_root_and_callee() {
+        let module = Module::new(
+            vec![VariableDeclaration::new(
+                "v",
+                types::Primitive::PointerInteger,
+            )],
+            vec![
+                FunctionDeclaration::new("d", create_function_type()),
+                FunctionDeclaration::new("e", create_function_type()),
+            ],
+            vec![],
+            vec![
+                create_function("f", Some("g")),
+                create_function("g", Some("d")),
+                create_function("h", Some("e")),
+            ],
+        );
+
+        assert_eq!(
+            retain_functions(&module, &["f"]),
+            Module::new(
+                vec![],
+                vec![FunctionDeclaration::new("d", create_function_type())],
+                vec![],
+                vec![
+                    create_function("f", Some("g")),
+                    create_function("g", Some("d")),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn retain_variable_referenced_by_function() {
+        let variable = VariableDefinition::new(
+            "v",
+            Variable::new("g"),
+            create_function_type(),
+            Default::default(),
+        );
+        let function = FunctionDefinition::new(
+            "f",
+            vec![],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![Load::new(types::Primitive::PointerInteger, Variable::new("v"), "x").into()],
+                Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+            ),
+            Default::default(),
+        );
+        let module = Module::new(
+            vec![],
+            vec![],
+            vec![variable.clone()],
+            vec![function.clone(), create_function("g", None)],
+        );
+
+        assert_eq!(
+            retain_functions(&module, &["f"]),
+            Module::new(
+                vec![],
+                vec![],
+                vec![variable],
+                vec![function, create_function("g", None)],
+            )
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use super::{alias, expression_conversion};
+use crate::{
+    ir::*,
+    types::{self, generic_pointer_type},
+};
+use fnv::FnvHashMap;
+
+// Move heap allocations of constant sizes up to a threshold in bytes onto stacks
+// if they do not escape from functions and are freed on every path.
+//
+// Only allocations in function bodies are moved so that they live until the
+// end of functions.
+pub fn transform(module: &mut Module, threshold: usize) {
+    for definition in module.function_definitions_mut() {
+        transform_function_definition(definition, threshold);
+    }
+}
+
+fn transform_function_definition(definition: &mut FunctionDefinition, threshold: usize) {
+    let allocations = collect_allocations(definition, threshold);
+
+    if allocations.is_empty() {
+        return;
+    }
+
+    transform_block(definition.body_mut(), &allocations);
+
+    let convert = |expression: &Expression| match expression {
+        Expression::Variable(variable) => match allocations.get(variable.name()) {
+            Some(type_) => BitCast::new(
+                types::Pointer::new(type_.clone()),
+                generic_pointer_type(),
+                variable.clone(),
+            )
+            .into(),
+            None => expression.clone(),
+        },
+        _ => expression.clone(),
+    };
+
+    for instruction in definition.body_mut().instructions_mut() {
+        expression_conversion::convert_in_instruction(instruction, &convert);
+    }
+
+    expression_conversion::convert_in_terminal_instruction(
+        definition.body_mut().terminal_instruction_mut(),
+        &convert,
+    );
+}
+
+fn collect_allocations(
+    definition: &FunctionDefinition,
+    threshold: usize,
+) -> FnvHashMap<String, types::Record> {
+    let names = alias::collect_non_escaping_allocations(definition);
+    let instructions = definition.body().instructions();
+
+    instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, instruction)| match instruction {
+            Instruction::AllocateHeap(allocate) => match allocate.size() {
+                Expression::Primitive(Primitive::PointerInteger(size))
+                    if *size as usize <= threshold
+                        && names.contains(allocate.name())
+                        && is_freed(&instructions[index + 1..], allocate.name()) =>
+                {
+                    Some((allocate.name().into(), create_type(*size as usize)))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+// Allocations are freed on every path if they are freed in function bodies
+// with no return in between.
+fn is_freed(instructions: &[Instruction], name: &str) -> bool {
+    for instruction in instructions {
+        match instruction {
+            Instruction::FreeHeap(free) if free.pointer() == &Variable::new(name).into() => {
+                return true;
+            }
+            Instruction::If(if_) if has_return(if_.then()) || has_return(if_.else_()) => {
+                return false;
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+fn has_return(block: &Block) -> bool {
+    matches!(block.terminal_instruction(), TerminalInstruction::Return(_))
+        || block
+            .instructions()
+            .iter()
+            .any(|instruction| match instruction {
+                Instruction::If(if_) => has_return(if_.then()) || has_return(if_.else_()),
+                _ => false,
+            })
+}
+
+// Use 8-byte words so that allocations are aligned for any primitive.
+fn create_type(size: usize) -> types::Record {
+    types::Record::new(vec![types::Primitive::Integer64.into(); size.div_ceil(8)])
+}
+
+fn transform_block(block: &mut Block, allocations: &FnvHashMap<String, types::Record>) {
+    block
+        .instructions_mut()
+        .retain(|instruction| match instruction {
+            Instruction::FreeHeap(free) => !matches!(
+                free.pointer(),
+                Expression::Variable(variable) if allocations.contains_key(variable.name())
+            ),
+            _ => true,
+        });
+
+    for instruction in block.instructions_mut() {
+        match instruction {
+            Instruction::AllocateHeap(allocate) => {
+                if let Some(type_) = allocations.get(allocate.name()) {
+                    *instruction = AllocateStack::new(type_.clone(), allocate.name()).into();
+                }
+            }
+            Instruction::If(if_) => {
+                transform_block(if_.then_mut(), allocations);
+                transform_block(if_.else_mut(), allocations);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::type_check, types::void_type};
+    use pretty_assertions::assert_eq;
+
+    const THRESHOLD: usize = 64;
+
+    fn create_module(instructions: Vec<Instruction>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                void_type(),
+                Block::new(instructions, Return::new(void_type(), void_value())),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module, THRESHOLD);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn move_small_allocation_to_stack() {
+        let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 2]);
+
+        assert_eq!(
+            transform_module(create_module(vec![
+                AllocateHeap::new(Primitive::PointerInteger(16), "x").into(),
+                Store::new(
+                    types::Primitive::Integer8,
+                    Primitive::Integer8(42),
+                    Variable::new("x"),
+                )
+                .into(),
+                FreeHeap::new(Variable::new("x")).into(),
+            ])),
+            create_module(vec![
+                AllocateStack::new(record_type.clone(), "x").into(),
+                Store::new(
+                    types::Primitive::Integer8,
+                    Primitive::Integer8(42),
+                    BitCast::new(
+                        types::Pointer::new(record_type),
+                        generic_pointer_type(),
+                        Variable::new("x"),
+                    ),
+                )
+                .into(),
+            ])
+        );
+    }
+
+    #[test]
+    fn keep_large_allocation_on_heap() {
+        let module = create_module(vec![
+            AllocateHeap::new(Primitive::PointerInteger(4096), "x").into(),
+            FreeHeap::new(Variable::new("x")).into(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_not_freed() {
+        let module = create_module(vec![
+            AllocateHeap::new(Primitive::PointerInteger(16), "x").into()
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_reallocated_allocation() {
+        let module = create_module(vec![
+            AllocateHeap::new(Primitive::PointerInteger(16), "x").into(),
+            ReallocateHeap::new(Variable::new("x"), Primitive::PointerInteger(32), "y").into(),
+            FreeHeap::new(Variable::new("y")).into(),
+            FreeHeap::new(Variable::new("x")).into(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    fn create_allocation() -> Instruction {
+        AllocateHeap::new(Primitive::PointerInteger(16), "x").into()
+    }
+
+    fn create_free() -> Instruction {
+        FreeHeap::new(Variable::new("x")).into()
+    }
+
+    fn create_if(then: Vec<Instruction>, else_: Vec<Instruction>) -> Instruction {
+        If::new(
+            void_type(),
+            Primitive::Boolean(true),
+            Block::new(then, Branch::new(void_type(), void_value())),
+            Block::new(else_, Branch::new(void_type(), void_value())),
+            "c",
+        )
+        .into()
+    }
+
+    #[test]
+    fn keep_returned_allocation() {
+        let module = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                generic_pointer_type(),
+                Block::new(
+                    vec![create_allocation(), create_free()],
+                    Return::new(generic_pointer_type(), Variable::new("x")),
+                ),
+                Default::default(),
+            )],
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_stored_allocation() {
+        let module = create_module(vec![
+            create_allocation(),
+            AllocateStack::new(generic_pointer_type(), "p").into(),
+            Store::new(
+                generic_pointer_type(),
+                Variable::new("x"),
+                Variable::new("p"),
+            )
+            .into(),
+            create_free(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_passed_to_call() {
+        let function_type = types::Function::new(
+            vec![generic_pointer_type()],
+            void_type(),
+            types::CallingConvention::Target,
+        );
+        let module = Module::new(
+            vec![],
+            vec![FunctionDeclaration::new("g", function_type.clone())],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                void_type(),
+                Block::new(
+                    vec![
+                        create_allocation(),
+                        Call::new(
+                            function_type,
+                            Variable::new("g"),
+                            vec![Variable::new("x").into()],
+                            "y",
+                        )
+                        .into(),
+                        create_free(),
+                    ],
+                    Return::new(void_type(), void_value()),
+                ),
+                Default::default(),
+            )],
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_branched_out_of_if() {
+        let module = create_module(vec![
+            create_allocation(),
+            If::new(
+                generic_pointer_type(),
+                Primitive::Boolean(true),
+                Block::new(
+                    vec![],
+                    Branch::new(generic_pointer_type(), Variable::new("x")),
+                ),
+                Block::new(
+                    vec![],
+                    Branch::new(generic_pointer_type(), Variable::new("x")),
+                ),
+                "y",
+            )
+            .into(),
+            create_free(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_freed_in_one_branch() {
+        let module = create_module(vec![
+            create_allocation(),
+            create_if(vec![create_free()], vec![]),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_with_return_before_free() {
+        let module = create_module(vec![
+            create_allocation(),
+            If::new(
+                void_type(),
+                Primitive::Boolean(true),
+                Block::new(vec![], Return::new(void_type(), void_value())),
+                Block::new(vec![], Branch::new(void_type(), void_value())),
+                "c",
+            )
+            .into(),
+            create_free(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_allocation_in_if() {
+        let module = create_module(vec![create_if(
+            vec![create_allocation(), create_free()],
+            vec![],
+        )]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
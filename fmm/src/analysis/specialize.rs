@@ -0,0 +1,373 @@
+use super::{expression_conversion, global_name};
+use crate::{ir::*, types};
+use fnv::{FnvHashMap, FnvHashSet};
+
+struct Specialization {
+    name: String,
+    type_: types::Function,
+    // Constants of arguments removed from calls
+    constants: Vec<Option<Primitive>>,
+}
+
+// Specialize functions whose direct calls all pass the same constants for some
+// arguments. Calls are redirected to specialized clones without those
+// arguments while original functions are kept for other references.
+pub fn transform(module: &mut Module) {
+    let mut calls = FnvHashMap::<String, Vec<Vec<Expression>>>::default();
+
+    for definition in module.function_definitions() {
+        collect_calls(definition.body(), &mut calls);
+    }
+
+    let mut names = global_name::collect(module);
+    let mut specializations = FnvHashMap::default();
+    let mut definitions = vec![];
+
+    for definition in module.function_definitions() {
+        let Some(arguments) = calls.get(definition.name()) else {
+            continue;
+        };
+        let constants = (0..definition.arguments().len())
+            .map(|index| find_constant(arguments, index))
+            .collect::<Vec<_>>();
+
+        if constants.iter().all(Option::is_none) {
+            continue;
+        }
+
+        let specialized = specialize_function_definition(definition, &constants, &mut names);
+
+        specializations.insert(
+            definition.name().to_owned(),
+            Specialization {
+                name: specialized.name().into(),
+                type_: specialized.type_(),
+                constants,
+            },
+        );
+        definitions.push(specialized);
+    }
+
+    if specializations.is_empty() {
+        return;
+    }
+
+    module.function_definitions_mut().extend(definitions);
+
+    for definition in module.function_definitions_mut() {
+        transform_block(definition.body_mut(), &specializations);
+    }
+}
+
+fn collect_calls(block: &Block, calls: &mut FnvHashMap<String, Vec<Vec<Expression>>>) {
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::Call(call) => {
+                if let Expression::Variable(variable) = call.function() {
+                    calls
+                        .entry(variable.name().into())
+                        .or_default()
+                        .push(call.arguments().to_vec());
+                }
+            }
+            Instruction::If(if_) => {
+                collect_calls(if_.then(), calls);
+                collect_calls(if_.else_(), calls);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn find_constant(arguments: &[Vec<Expression>], index: usize) -> Option<Primitive> {
+    let Some(Expression::Primitive(constant)) = arguments.first()?.get(index) else {
+        return None;
+    };
+
+    arguments
+        .iter()
+        .all(|arguments| arguments.get(index) == Some(&Expression::Primitive(*constant)))
+        .then_some(*constant)
+}
+
+fn specialize_function_definition(
+    definition: &FunctionDefinition,
+    constants: &[Option<Primitive>],
+    names: &mut FnvHashSet<String>,
+) -> FunctionDefinition {
+    let values = definition
+        .arguments()
+        .iter()
+        .zip(constants)
+        .filter_map(|(argument, constant)| Some((argument.name(), (*constant)?)))
+        .collect::<FnvHashMap<_, _>>();
+    let mut body = definition.body().clone();
+
+    convert_block(&mut body, &|expression| match expression {
+        Expression::Variable(variable) => values
+            .get(variable.name())
+            .map(|&constant| constant.into())
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    });
+
+    FunctionDefinition::new(
+        global_name::generate(&format!("{}_specialized", definition.name()), names),
+        definition
+            .arguments()
+            .iter()
+            .zip(constants)
+            .filter(|(_, constant)| constant.is_none())
+            .map(|(argument, _)| argument.clone())
+            .collect(),
+        definition.result_type().clone(),
+        body,
+        definition.options().clone().set_linkage(Linkage::Internal),
+    )
+}
+
+fn convert_block(block: &mut Block, convert: &impl Fn(&Expression) -> Expression) {
+    for instruction in block.instructions_mut() {
+        expression_conversion::convert_in_instruction(instruction, convert);
+    }
+
+    expression_conversion::convert_in_terminal_instruction(
+        block.terminal_instruction_mut(),
+        convert,
+    );
+}
+
+fn transform_block(block: &mut Block, specializations: &FnvHashMap<String, Specialization>) {
+    for instruction in block.instructions_mut() {
+        match instruction {
+            Instruction::Call(call) => {
+                let Expression::Variable(variable) = call.function() else {
+                    continue;
+                };
+                let Some(specialization) = specializations.get(variable.name()) else {
+                    continue;
+                };
+
+                *call.function_mut() = Variable::new(&specialization.name).into();
+                *call.type_mut() = specialization.type_.clone();
+                *call.arguments_mut() = call
+                    .arguments()
+                    .iter()
+                    .zip(&specialization.constants)
+                    .filter(|(_, constant)| constant.is_none())
+                    .map(|(argument, _)| argument.clone())
+                    .collect();
+            }
+            Instruction::If(if_) => {
+                transform_block(if_.then_mut(), specializations);
+                transform_block(if_.else_mut(), specializations);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{name, type_check};
+    use pretty_assertions::assert_eq;
+
+    fn create_callee() -> FunctionDefinition {
+        FunctionDefinition::new(
+            "f",
+            vec![
+                Argument::new("x", types::Primitive::Boolean),
+                Argument::new("y", types::Primitive::PointerInteger),
+            ],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![If::new(
+                    types::Primitive::PointerInteger,
+                    Variable::new("x"),
+                    Block::new(
+                        vec![],
+                        Branch::new(types::Primitive::PointerInteger, Variable::new("y")),
+                    ),
+                    Block::new(
+                        vec![],
+                        Branch::new(
+                            types::Primitive::PointerInteger,
+                            Primitive::PointerInteger(0),
+                        ),
+                    ),
+                    "z",
+                )
+                .into()],
+                Return::new(types::Primitive::PointerInteger, Variable::new("z")),
+            ),
+            Default::default(),
+        )
+    }
+
+    fn create_caller(
+        name: &str,
+        flag: impl Into<Expression>,
+        value: impl Into<Expression>,
+    ) -> FunctionDefinition {
+        FunctionDefinition::new(
+            name,
+            vec![
+                Argument::new("a", types::Primitive::Boolean),
+                Argument::new("c", types::Primitive::PointerInteger),
+            ],
+            types::Primitive::PointerInteger,
+            Block::new(
+                vec![Call::new(
+                    create_callee().type_(),
+                    Variable::new("f"),
+                    vec![flag.into(), value.into()],
+                    "b",
+                )
+                .into()],
+                Return::new(types::Primitive::PointerInteger, Variable::new("b")),
+            ),
+            Default::default(),
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn specialize_function_called_with_constant() {
+        let module = transform_module(Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_callee(),
+                create_caller("g", Primitive::Boolean(true), Primitive::PointerInteger(42)),
+                create_caller("h", Primitive::Boolean(true), Primitive::PointerInteger(42)),
+            ],
+        ));
+
+        assert_eq!(
+            module.function_definitions()[3],
+            FunctionDefinition::new(
+                "f_specialized_0",
+                vec![],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    vec![If::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::Boolean(true),
+                        Block::new(
+                            vec![],
+                            Branch::new(
+                                types::Primitive::PointerInteger,
+                                Primitive::PointerInteger(42),
+                            ),
+                        ),
+                        Block::new(
+                            vec![],
+                            Branch::new(
+                                types::Primitive::PointerInteger,
+                                Primitive::PointerInteger(0),
+                            ),
+                        ),
+                        "z",
+                    )
+                    .into()],
+                    Return::new(types::Primitive::PointerInteger, Variable::new("z")),
+                ),
+                FunctionDefinitionOptions::new().set_linkage(Linkage::Internal),
+            )
+        );
+        assert_eq!(
+            module.function_definitions()[1].body().instructions(),
+            &[Call::new(
+                types::Function::new(
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    types::CallingConvention::Source,
+                ),
+                Variable::new("f_specialized_0"),
+                vec![],
+                "b",
+            )
+            .into()]
+        );
+    }
+
+    #[test]
+    fn keep_arguments_with_different_values() {
+        let module = transform_module(Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_callee(),
+                create_caller("g", Primitive::Boolean(true), Variable::new("c")),
+                create_caller("h", Primitive::Boolean(true), Primitive::PointerInteger(42)),
+            ],
+        ));
+        let specialized = &module.function_definitions()[3];
+
+        assert_eq!(
+            specialized.arguments(),
+            &[Argument::new("y", types::Primitive::PointerInteger)]
+        );
+        assert_eq!(
+            module.function_definitions()[1].body().instructions(),
+            &[Call::new(
+                specialized.type_(),
+                Variable::new("f_specialized_0"),
+                vec![Variable::new("c").into()],
+                "b",
+            )
+            .into()]
+        );
+    }
+
+    #[test]
+    fn keep_function_called_with_different_constants() {
+        let module = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![
+                create_callee(),
+                create_caller("g", Primitive::Boolean(true), Primitive::PointerInteger(1)),
+                create_caller("h", Primitive::Boolean(false), Primitive::PointerInteger(2)),
+            ],
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn generate_name_different_from_existing_global_names() {
+        let module = transform_module(Module::new(
+            vec![],
+            vec![FunctionDeclaration::new(
+                "f_specialized_0",
+                types::Function::new(
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    types::CallingConvention::Source,
+                ),
+            )],
+            vec![],
+            vec![
+                create_callee(),
+                create_caller("g", Primitive::Boolean(true), Primitive::PointerInteger(42)),
+            ],
+        ));
+
+        name::check(&module).unwrap();
+        assert_eq!(module.function_definitions()[2].name(), "f_specialized_1");
+    }
+}
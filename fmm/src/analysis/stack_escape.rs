@@ -0,0 +1,350 @@
+mod error;
+
+use super::alias::find_base;
+use crate::ir::*;
+pub use error::*;
+use fnv::FnvHashMap;
+
+// Checks if pointers derived from stack allocations are returned from functions
+// or stored into memory other than stack allocations.
+pub fn check(module: &Module) -> Result<(), StackEscapeError> {
+    for definition in module.function_definitions() {
+        let mut names = FnvHashMap::default();
+
+        collect_allocations(definition.body(), &mut names);
+
+        if !names.is_empty() {
+            check_block(definition.body(), &mut names)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_allocations<'a>(block: &'a Block, names: &mut FnvHashMap<&'a str, &'a str>) {
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::AllocateStack(allocate) => {
+                names.insert(allocate.name(), allocate.name());
+            }
+            Instruction::If(if_) => {
+                collect_allocations(if_.then(), names);
+                collect_allocations(if_.else_(), names);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Names of variables are mapped to names of stack allocations which their
+// values point to. Results of if instructions are added as they are found.
+fn check_block<'a>(
+    block: &'a Block,
+    names: &mut FnvHashMap<&'a str, &'a str>,
+) -> Result<(), StackEscapeError> {
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::AtomicOperation(operation) => {
+                check_stored_value(operation.value(), operation.pointer(), names)?
+            }
+            Instruction::AtomicStore(store) => {
+                check_stored_value(store.value(), store.pointer(), names)?
+            }
+            Instruction::CompareAndSwap(cas) => {
+                check_stored_value(cas.new_value(), cas.pointer(), names)?
+            }
+            Instruction::If(if_) => {
+                check_block(if_.then(), names)?;
+                check_block(if_.else_(), names)?;
+
+                if let Some(name) = [if_.then(), if_.else_()].into_iter().find_map(|block| {
+                    match block.terminal_instruction() {
+                        TerminalInstruction::Branch(branch) => {
+                            find_allocation(branch.expression(), names)
+                        }
+                        _ => None,
+                    }
+                }) {
+                    names.insert(if_.name(), name);
+                }
+            }
+            Instruction::Store(store) => check_stored_value(store.value(), store.pointer(), names)?,
+            _ => {}
+        }
+    }
+
+    if let TerminalInstruction::Return(return_) = block.terminal_instruction() {
+        if let Some(name) = find_allocation(return_.expression(), names) {
+            return Err(StackEscapeError::ReturnedStackPointer(name.into()));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_stored_value(
+    value: &Expression,
+    pointer: &Expression,
+    names: &FnvHashMap<&str, &str>,
+) -> Result<(), StackEscapeError> {
+    if let Some(name) = find_allocation(value, names) {
+        if find_allocation(pointer, names).is_none() {
+            return Err(StackEscapeError::StoredStackPointer(name.into()));
+        }
+    }
+
+    Ok(())
+}
+
+// Pointers wrapped in records or unions are found as well.
+fn find_allocation<'a>(
+    expression: &Expression,
+    names: &FnvHashMap<&'a str, &'a str>,
+) -> Option<&'a str> {
+    match expression {
+        Expression::Record(record) => record
+            .fields()
+            .iter()
+            .find_map(|field| find_allocation(field, names)),
+        Expression::Union(union) => find_allocation(union.member(), names),
+        _ => find_base(expression).and_then(|name| names.get(name).copied()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+
+    fn create_module(
+        instructions: Vec<Instruction>,
+        result_type: impl Into<types::Type>,
+        result: impl Into<Expression>,
+    ) -> Module {
+        let result_type = result_type.into();
+
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new(
+                    "p",
+                    types::Pointer::new(types::generic_pointer_type()),
+                )],
+                result_type.clone(),
+                Block::new(instructions, Return::new(result_type, result)),
+                Default::default(),
+            )],
+        )
+    }
+
+    #[test]
+    fn fail_to_check_returned_stack_pointer() {
+        assert_eq!(
+            check(&create_module(
+                vec![AllocateStack::new(types::Primitive::Integer8, "x").into()],
+                types::generic_pointer_type(),
+                Variable::new("x"),
+            )),
+            Err(StackEscapeError::ReturnedStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_returned_derived_stack_pointer() {
+        let record_type = types::Record::new(vec![types::Primitive::Integer8.into()]);
+
+        assert_eq!(
+            check(&create_module(
+                vec![AllocateStack::new(record_type.clone(), "x").into()],
+                types::generic_pointer_type(),
+                RecordAddress::new(record_type, Variable::new("x"), 0),
+            )),
+            Err(StackEscapeError::ReturnedStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_stored_stack_pointer() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::Integer8, "x").into(),
+                    Store::new(
+                        types::generic_pointer_type(),
+                        Variable::new("x"),
+                        Variable::new("p"),
+                    )
+                    .into(),
+                ],
+                types::void_type(),
+                void_value(),
+            )),
+            Err(StackEscapeError::StoredStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn check_stack_pointer_stored_in_stack() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::Integer8, "x").into(),
+                    AllocateStack::new(types::generic_pointer_type(), "y").into(),
+                    Store::new(
+                        types::generic_pointer_type(),
+                        Variable::new("x"),
+                        Variable::new("y"),
+                    )
+                    .into(),
+                ],
+                types::void_type(),
+                void_value(),
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fail_to_check_returned_stack_pointer_branched_out_of_if() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::Integer8, "x").into(),
+                    If::new(
+                        types::generic_pointer_type(),
+                        Primitive::Boolean(true),
+                        Block::new(
+                            vec![],
+                            Branch::new(types::generic_pointer_type(), Variable::new("x"))
+                        ),
+                        Block::new(
+                            vec![],
+                            Branch::new(
+                                types::generic_pointer_type(),
+                                Undefined::new(types::generic_pointer_type())
+                            )
+                        ),
+                        "y",
+                    )
+                    .into(),
+                ],
+                types::generic_pointer_type(),
+                Variable::new("y"),
+            )),
+            Err(StackEscapeError::ReturnedStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_returned_stack_pointer_in_record() {
+        let record_type = types::Record::new(vec![types::generic_pointer_type()]);
+
+        assert_eq!(
+            check(&create_module(
+                vec![AllocateStack::new(types::Primitive::Integer8, "x").into()],
+                record_type.clone(),
+                Record::new(record_type, vec![Variable::new("x").into()]),
+            )),
+            Err(StackEscapeError::ReturnedStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_stored_stack_pointer_in_union() {
+        let union_type = types::Union::new(vec![
+            types::generic_pointer_type(),
+            types::Primitive::Integer64.into(),
+        ]);
+
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::Integer8, "x").into(),
+                    Store::new(
+                        union_type.clone(),
+                        Union::new(union_type.clone(), 0, Variable::new("x")),
+                        BitCast::new(
+                            types::Pointer::new(types::generic_pointer_type()),
+                            types::Pointer::new(union_type),
+                            Variable::new("p"),
+                        ),
+                    )
+                    .into(),
+                ],
+                types::void_type(),
+                void_value(),
+            )),
+            Err(StackEscapeError::StoredStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_stack_pointer_swapped_into_memory() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::Integer8, "x").into(),
+                    CompareAndSwap::new(
+                        types::generic_pointer_type(),
+                        Variable::new("p"),
+                        Undefined::new(types::generic_pointer_type()),
+                        Variable::new("x"),
+                        AtomicOrdering::SequentiallyConsistent,
+                        AtomicOrdering::SequentiallyConsistent,
+                        "y",
+                    )
+                    .into(),
+                ],
+                types::void_type(),
+                void_value(),
+            )),
+            Err(StackEscapeError::StoredStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_stack_pointer_in_atomic_operation() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::Integer8, "x").into(),
+                    AtomicOperation::new(
+                        types::Primitive::PointerInteger,
+                        AtomicOperator::Add,
+                        BitCast::new(
+                            types::Pointer::new(types::generic_pointer_type()),
+                            types::Pointer::new(types::Primitive::PointerInteger),
+                            Variable::new("p"),
+                        ),
+                        BitCast::new(
+                            types::generic_pointer_type(),
+                            types::Primitive::PointerInteger,
+                            Variable::new("x"),
+                        ),
+                        AtomicOrdering::SequentiallyConsistent,
+                        "y",
+                    )
+                    .into(),
+                ],
+                types::void_type(),
+                void_value(),
+            )),
+            Err(StackEscapeError::StoredStackPointer("x".into()))
+        );
+    }
+
+    #[test]
+    fn check_returned_heap_pointer() {
+        assert_eq!(
+            check(&create_module(
+                vec![AllocateHeap::new(Primitive::PointerInteger(1), "x").into()],
+                types::generic_pointer_type(),
+                Variable::new("x"),
+            )),
+            Ok(())
+        );
+    }
+}
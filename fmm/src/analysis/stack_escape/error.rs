@@ -0,0 +1,18 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StackEscapeError {
+    ReturnedStackPointer(String),
+    StoredStackPointer(String),
+}
+
+impl Display for StackEscapeError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}", self)
+    }
+}
+
+impl Error for StackEscapeError {}
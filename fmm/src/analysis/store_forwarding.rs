@@ -0,0 +1,283 @@
+use super::{expression_conversion, variable_use};
+use crate::{ir::*, types::Type};
+use fnv::{FnvHashMap, FnvHashSet};
+use std::mem::take;
+
+// Forward values stored into stack allocations to subsequent loads within
+// blocks. Only allocations used directly by loads and stores are considered.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_function_definition(definition);
+    }
+}
+
+fn transform_function_definition(definition: &mut FunctionDefinition) {
+    let counts = variable_use::count(definition);
+    let mut allocated_names = FnvHashSet::default();
+    let mut pointer_counts = FnvHashMap::default();
+
+    collect_pointers(definition.body(), &mut allocated_names, &mut pointer_counts);
+
+    let names = allocated_names
+        .into_iter()
+        .filter(|&name| counts.get(name) == pointer_counts.get(name))
+        .map(String::from)
+        .collect::<FnvHashSet<_>>();
+
+    if names.is_empty() {
+        return;
+    }
+
+    transform_block(definition.body_mut(), &names, &mut Default::default());
+}
+
+fn collect_pointers<'a>(
+    block: &'a Block,
+    allocated_names: &mut FnvHashSet<&'a str>,
+    pointer_counts: &mut FnvHashMap<&'a str, usize>,
+) {
+    for instruction in block.instructions() {
+        let pointer = match instruction {
+            Instruction::AllocateStack(allocate) => {
+                allocated_names.insert(allocate.name());
+                None
+            }
+            Instruction::If(if_) => {
+                collect_pointers(if_.then(), allocated_names, pointer_counts);
+                collect_pointers(if_.else_(), allocated_names, pointer_counts);
+                None
+            }
+            Instruction::Load(load) => Some(load.pointer()),
+            Instruction::Store(store) => Some(store.pointer()),
+            _ => None,
+        };
+
+        if let Some(Expression::Variable(variable)) = pointer {
+            *pointer_counts.entry(variable.name()).or_default() += 1;
+        }
+    }
+}
+
+fn transform_block(
+    block: &mut Block,
+    names: &FnvHashSet<String>,
+    forwarded_values: &mut FnvHashMap<String, Expression>,
+) {
+    let mut stored_values = FnvHashMap::<String, (Type, Expression)>::default();
+    let mut instructions = Vec::with_capacity(block.instructions().len());
+
+    for mut instruction in take(block.instructions_mut()) {
+        expression_conversion::convert_in_instruction(&mut instruction, &|expression| {
+            forward_value(expression, forwarded_values)
+        });
+
+        match &mut instruction {
+            Instruction::Call(_) => stored_values.clear(),
+            Instruction::If(if_) => {
+                transform_block(if_.then_mut(), names, forwarded_values);
+                transform_block(if_.else_mut(), names, forwarded_values);
+
+                stored_values.clear();
+            }
+            Instruction::Load(load) => {
+                if let Expression::Variable(variable) = load.pointer() {
+                    if let Some((type_, value)) = stored_values.get(variable.name()) {
+                        if type_ == load.type_() {
+                            forwarded_values.insert(load.name().into(), value.clone());
+                            continue;
+                        }
+                    }
+                }
+            }
+            Instruction::Store(store) => {
+                if let Expression::Variable(variable) = store.pointer() {
+                    if names.contains(variable.name()) {
+                        stored_values.insert(
+                            variable.name().into(),
+                            (store.type_().clone(), store.value().clone()),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        instructions.push(instruction);
+    }
+
+    *block.instructions_mut() = instructions;
+
+    expression_conversion::convert_in_terminal_instruction(
+        block.terminal_instruction_mut(),
+        &|expression| forward_value(expression, forwarded_values),
+    );
+}
+
+fn forward_value(
+    expression: &Expression,
+    forwarded_values: &FnvHashMap<String, Expression>,
+) -> Expression {
+    match expression {
+        Expression::Variable(variable) => forwarded_values
+            .get(variable.name())
+            .cloned()
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{self, void_type};
+    use pretty_assertions::assert_eq;
+
+    fn create_module(instructions: Vec<Instruction>, result: impl Into<Expression>) -> Module {
+        Module::new(
+            vec![],
+            vec![FunctionDeclaration::new(
+                "g",
+                types::Function::new(vec![], void_type(), types::CallingConvention::Target),
+            )],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    instructions,
+                    Return::new(types::Primitive::PointerInteger, result),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        transform(&mut module);
+
+        module
+    }
+
+    #[test]
+    fn forward_store_to_load() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                    Store::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(42),
+                        Variable::new("x"),
+                    )
+                    .into(),
+                    Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+                ],
+                Variable::new("y"),
+            )),
+            create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                    Store::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(42),
+                        Variable::new("x"),
+                    )
+                    .into(),
+                ],
+                Primitive::PointerInteger(42),
+            )
+        );
+    }
+
+    #[test]
+    fn forward_latest_store_to_load() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                    Store::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(1),
+                        Variable::new("x"),
+                    )
+                    .into(),
+                    Store::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(2),
+                        Variable::new("x"),
+                    )
+                    .into(),
+                    Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+                ],
+                Variable::new("y"),
+            )),
+            create_module(
+                vec![
+                    AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                    Store::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(1),
+                        Variable::new("x"),
+                    )
+                    .into(),
+                    Store::new(
+                        types::Primitive::PointerInteger,
+                        Primitive::PointerInteger(2),
+                        Variable::new("x"),
+                    )
+                    .into(),
+                ],
+                Primitive::PointerInteger(2),
+            )
+        );
+    }
+
+    #[test]
+    fn keep_load_after_call() {
+        let module = create_module(
+            vec![
+                AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                Store::new(
+                    types::Primitive::PointerInteger,
+                    Primitive::PointerInteger(42),
+                    Variable::new("x"),
+                )
+                .into(),
+                Call::new(
+                    types::Function::new(vec![], void_type(), types::CallingConvention::Target),
+                    Variable::new("g"),
+                    vec![],
+                    "z",
+                )
+                .into(),
+                Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+            ],
+            Variable::new("y"),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn keep_load_from_escaped_pointer() {
+        let pointer_type = types::Pointer::new(types::Primitive::PointerInteger);
+        let module = create_module(
+            vec![
+                AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                AllocateStack::new(pointer_type.clone(), "p").into(),
+                Store::new(pointer_type, Variable::new("x"), Variable::new("p")).into(),
+                Store::new(
+                    types::Primitive::PointerInteger,
+                    Primitive::PointerInteger(42),
+                    Variable::new("x"),
+                )
+                .into(),
+                Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y").into(),
+            ],
+            Variable::new("y"),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -0,0 +1,225 @@
+mod error;
+
+use super::c_calling_convention;
+use crate::{ir::*, types};
+pub use error::*;
+
+// Checks if must-tail calls are in tail positions and their callees have
+// signatures compatible with their callers. Errors contain names of calls.
+pub fn check(module: &Module, word_bytes: usize) -> Result<(), TailCallError> {
+    check_module(module, Some(word_bytes))
+}
+
+// Checks only positions of must-tail calls for backends independent of word
+// sizes.
+pub fn check_positions(module: &Module) -> Result<(), TailCallError> {
+    check_module(module, None)
+}
+
+// Finds a must-tail call at the end of a block whose result is returned
+// directly.
+pub fn find_must_tail_call(block: &Block) -> Option<&Call> {
+    match (block.instructions().last(), block.terminal_instruction()) {
+        (Some(Instruction::Call(call)), TerminalInstruction::Return(return_))
+            if call.tail_call() == TailCall::MustTail
+                && (return_.expression() == &Variable::new(call.name()).into()
+                    || call.type_().result() == &types::void_type().into()
+                        && return_.expression() == &void_value().into()) =>
+        {
+            Some(call)
+        }
+        _ => None,
+    }
+}
+
+fn check_module(module: &Module, word_bytes: Option<usize>) -> Result<(), TailCallError> {
+    for definition in module.function_definitions() {
+        check_block(definition.body(), &definition.type_(), word_bytes)?;
+    }
+
+    Ok(())
+}
+
+fn check_block(
+    block: &Block,
+    caller: &types::Function,
+    word_bytes: Option<usize>,
+) -> Result<(), TailCallError> {
+    let tail_call = find_must_tail_call(block);
+
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::Call(call) if call.tail_call() == TailCall::MustTail => {
+                if !tail_call
+                    .map(|tail_call| std::ptr::eq(tail_call, call))
+                    .unwrap_or_default()
+                {
+                    return Err(TailCallError::NotInTailPosition(call.name().into()));
+                } else if let Some(word_bytes) = word_bytes {
+                    if !is_compatible(caller, call.type_(), word_bytes) {
+                        return Err(TailCallError::IncompatibleSignature(call.name().into()));
+                    }
+                }
+            }
+            Instruction::If(if_) => {
+                check_block(if_.then(), caller, word_bytes)?;
+                check_block(if_.else_(), caller, word_bytes)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn is_compatible(caller: &types::Function, callee: &types::Function, word_bytes: usize) -> bool {
+    match (caller.calling_convention(), callee.calling_convention()) {
+        (types::CallingConvention::Source, types::CallingConvention::Source) => {
+            caller.result() == callee.result()
+        }
+        (types::CallingConvention::Target, types::CallingConvention::Target) => {
+            c_calling_convention::is_tail_call_compatible(caller, callee, word_bytes)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WORD_BYTES: usize = 8;
+
+    fn create_module(result_type: impl Into<types::Type>, tail_call: TailCall) -> Module {
+        let result_type = result_type.into();
+        let function_type = types::Function::new(
+            vec![],
+            result_type.clone(),
+            types::CallingConvention::Target,
+        );
+
+        Module::new(
+            vec![],
+            vec![FunctionDeclaration::new("g", function_type.clone())],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                result_type.clone(),
+                Block::new(
+                    vec![Call::new(function_type, Variable::new("g"), vec![], "x")
+                        .set_tail_call(tail_call)
+                        .into()],
+                    Return::new(result_type, Variable::new("x")),
+                ),
+                FunctionDefinitionOptions::new()
+                    .set_calling_convention(types::CallingConvention::Target),
+            )],
+        )
+    }
+
+    #[test]
+    fn check_must_tail_call() {
+        assert_eq!(
+            check(
+                &create_module(types::Primitive::Integer64, TailCall::MustTail),
+                WORD_BYTES
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fail_to_check_must_tail_call_with_struct_return() {
+        assert_eq!(
+            check(
+                &create_module(
+                    types::Record::new(vec![types::Primitive::Integer64.into(); 3]),
+                    TailCall::MustTail
+                ),
+                WORD_BYTES
+            ),
+            Err(TailCallError::IncompatibleSignature("x".into()))
+        );
+    }
+
+    #[test]
+    fn check_call_with_struct_return() {
+        assert_eq!(
+            check(
+                &create_module(
+                    types::Record::new(vec![types::Primitive::Integer64.into(); 3]),
+                    TailCall::Hint
+                ),
+                WORD_BYTES
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fail_to_check_must_tail_call_not_in_tail_position() {
+        let function_type = types::Function::new(
+            vec![],
+            types::Primitive::Integer64,
+            types::CallingConvention::Target,
+        );
+
+        assert_eq!(
+            check(
+                &Module::new(
+                    vec![],
+                    vec![FunctionDeclaration::new("g", function_type.clone())],
+                    vec![],
+                    vec![FunctionDefinition::new(
+                        "f",
+                        vec![],
+                        types::Primitive::Integer64,
+                        Block::new(
+                            vec![Call::new(function_type, Variable::new("g"), vec![], "x")
+                                .set_tail_call(TailCall::MustTail)
+                                .into()],
+                            Return::new(types::Primitive::Integer64, Primitive::Integer64(42)),
+                        ),
+                        FunctionDefinitionOptions::new()
+                            .set_calling_convention(types::CallingConvention::Target),
+                    )],
+                ),
+                WORD_BYTES
+            ),
+            Err(TailCallError::NotInTailPosition("x".into()))
+        );
+    }
+
+    #[test]
+    fn check_must_tail_call_of_void_result() {
+        let function_type =
+            types::Function::new(vec![], types::void_type(), types::CallingConvention::Target);
+
+        assert_eq!(
+            check(
+                &Module::new(
+                    vec![],
+                    vec![FunctionDeclaration::new("g", function_type.clone())],
+                    vec![],
+                    vec![FunctionDefinition::new(
+                        "f",
+                        vec![],
+                        types::void_type(),
+                        Block::new(
+                            vec![Call::new(function_type, Variable::new("g"), vec![], "x")
+                                .set_tail_call(TailCall::MustTail)
+                                .into()],
+                            Return::new(types::void_type(), void_value()),
+                        ),
+                        FunctionDefinitionOptions::new()
+                            .set_calling_convention(types::CallingConvention::Target),
+                    )],
+                ),
+                WORD_BYTES
+            ),
+            Ok(())
+        );
+    }
+}
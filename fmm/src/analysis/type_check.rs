@@ -98,11 +98,24 @@ fn check_function_definition<'a>(
     definition: &'a FunctionDefinition,
     variables: &mut FnvHashMap<&'a str, Type>,
 ) -> Result<(), TypeCheckError> {
+    if let Some(alignment) = definition.options().alignment() {
+        if !alignment.is_power_of_two() {
+            return Err(TypeCheckError::InvalidAlignment(alignment));
+        }
+    }
+
     let local_variables = local_variable::collect(definition);
 
     variables.extend(local_variables.clone());
 
-    check_block(definition.body(), definition.result_type(), None, variables)?;
+    check_block(
+        definition.name(),
+        definition.body(),
+        definition.result_type(),
+        None,
+        &mut vec![],
+        variables,
+    )?;
 
     for name in local_variables.keys() {
         variables.remove(name);
@@ -111,168 +124,229 @@ fn check_function_definition<'a>(
     Ok(())
 }
 
+// Errors in instructions are reported with their paths.
 fn check_block(
+    function_name: &str,
     block: &Block,
     result_type: &Type,
     branch_type: Option<&Type>,
+    branches: &mut Vec<(usize, IfBranch)>,
     variables: &FnvHashMap<&str, Type>,
 ) -> Result<(), TypeCheckError> {
-    for instruction in block.instructions() {
-        match instruction {
-            Instruction::AllocateHeap(allocate) => {
-                check_equality(
-                    &check_expression(allocate.size(), variables)?,
-                    &types::Primitive::PointerInteger.into(),
-                )?;
-            }
-            Instruction::AllocateStack(_) => {}
-            Instruction::AtomicLoad(load) => {
-                check_equality(
-                    &check_expression(load.pointer(), variables)?,
-                    &types::Pointer::new(load.type_().clone()).clone().into(),
-                )?;
-            }
-            Instruction::AtomicOperation(operation) => {
-                check_equality(
-                    &check_expression(operation.pointer(), variables)?,
-                    &types::Pointer::new(operation.type_()).into(),
-                )?;
-                check_equality(
-                    &check_expression(operation.value(), variables)?,
-                    &operation.type_().into(),
-                )?;
-            }
-            Instruction::AtomicStore(store) => {
-                check_equality(
-                    &check_expression(store.value(), variables)?,
-                    &store.type_().clone(),
-                )?;
-                check_equality(
-                    &check_expression(store.pointer(), variables)?,
-                    &types::Pointer::new(store.type_().clone()).into(),
+    for (index, instruction) in block.instructions().iter().enumerate() {
+        check_instruction(instruction, variables).map_err(|error| {
+            TypeCheckError::Instruction(
+                InstructionPath::new(function_name, branches.clone(), index),
+                error.into(),
+            )
+        })?;
+
+        if let Instruction::If(if_) = instruction {
+            for (branch, block) in [(IfBranch::Then, if_.then()), (IfBranch::Else, if_.else_())] {
+                branches.push((index, branch));
+                check_block(
+                    function_name,
+                    block,
+                    result_type,
+                    Some(if_.type_()),
+                    branches,
+                    variables,
                 )?;
+                branches.pop();
             }
-            Instruction::Call(call) => {
-                if call.arguments().len() != call.type_().arguments().len() {
-                    return Err(TypeCheckError::FunctionArguments(call.clone()));
-                }
+        }
+    }
 
-                check_equality(
-                    &call.type_().clone().into(),
-                    &check_expression(call.function(), variables)?,
-                )?;
+    match block.terminal_instruction() {
+        TerminalInstruction::Branch(branch) => {
+            let branch_type =
+                branch_type.ok_or_else(|| TypeCheckError::InvalidBranch(branch.clone()))?;
 
-                for (argument, type_) in call.arguments().iter().zip(call.type_().arguments()) {
-                    check_equality(&check_expression(argument, variables)?, type_)?;
-                }
-            }
-            Instruction::CompareAndSwap(cas) => {
-                check_equality(
-                    &check_expression(cas.pointer(), variables)?,
-                    &types::Pointer::new(cas.type_().clone()).into(),
-                )?;
+            check_equality(branch.type_(), branch_type)?;
+            check_equality(
+                &check_expression(branch.expression(), variables)?,
+                branch_type,
+            )?;
+        }
+        TerminalInstruction::Return(return_) => {
+            check_equality(return_.type_(), result_type)?;
+            check_equality(
+                &check_expression(return_.expression(), variables)?,
+                result_type,
+            )?;
+        }
+        TerminalInstruction::Unreachable => {}
+    }
 
-                check_equality(
-                    &check_expression(cas.old_value(), variables)?,
-                    &cas.type_().clone(),
-                )?;
+    Ok(())
+}
 
+// Blocks of if instructions are not checked.
+pub(crate) fn check_instruction(
+    instruction: &Instruction,
+    variables: &FnvHashMap<&str, Type>,
+) -> Result<(), TypeCheckError> {
+    match instruction {
+        Instruction::AllocateHeap(allocate) => {
+            check_equality(
+                &check_expression(allocate.size(), variables)?,
+                &types::Primitive::PointerInteger.into(),
+            )?;
+        }
+        Instruction::AllocateStack(allocate) => {
+            if let Some(count) = allocate.count() {
                 check_equality(
-                    &check_expression(cas.new_value(), variables)?,
-                    &cas.type_().clone(),
+                    &check_expression(count, variables)?,
+                    &types::Primitive::PointerInteger.into(),
                 )?;
             }
-            Instruction::DeconstructRecord(deconstruct) => {
-                check_equality(
-                    &check_expression(deconstruct.record(), variables)?,
-                    &deconstruct.type_().clone().into(),
-                )?;
-
-                check_record_index(deconstruct.field_index(), deconstruct.type_())?;
+        }
+        Instruction::AtomicLoad(load) => {
+            if matches!(
+                load.ordering(),
+                AtomicOrdering::Release | AtomicOrdering::AcquireRelease
+            ) {
+                return Err(TypeCheckError::InvalidAtomicOrdering(instruction.clone()));
             }
-            Instruction::DeconstructUnion(deconstruct) => {
-                check_equality(
-                    &check_expression(deconstruct.union(), variables)?,
-                    &deconstruct.type_().clone().into(),
-                )?;
 
-                check_union_index(deconstruct.member_index(), deconstruct.type_())?;
+            check_equality(
+                &check_expression(load.pointer(), variables)?,
+                &types::Pointer::new(load.type_().clone()).clone().into(),
+            )?;
+        }
+        Instruction::AtomicOperation(operation) => {
+            check_equality(
+                &check_expression(operation.pointer(), variables)?,
+                &types::Pointer::new(operation.type_()).into(),
+            )?;
+            check_equality(
+                &check_expression(operation.value(), variables)?,
+                &operation.type_().into(),
+            )?;
+        }
+        Instruction::AtomicStore(store) => {
+            if matches!(
+                store.ordering(),
+                AtomicOrdering::Acquire | AtomicOrdering::AcquireRelease
+            ) {
+                return Err(TypeCheckError::InvalidAtomicOrdering(instruction.clone()));
             }
-            Instruction::Fence(_) => {}
-            Instruction::FreeHeap(free) => {
-                check_equality(
-                    &check_expression(free.pointer(), variables)?,
-                    &generic_pointer_type(),
-                )?;
+
+            check_equality(
+                &check_expression(store.value(), variables)?,
+                &store.type_().clone(),
+            )?;
+            check_equality(
+                &check_expression(store.pointer(), variables)?,
+                &types::Pointer::new(store.type_().clone()).into(),
+            )?;
+        }
+        Instruction::Call(call) => {
+            if call.arguments().len() != call.type_().arguments().len() {
+                return Err(TypeCheckError::FunctionArguments(call.clone()));
             }
-            Instruction::If(if_) => {
-                check_equality(
-                    &check_expression(if_.condition(), variables)?,
-                    &types::Primitive::Boolean.into(),
-                )?;
 
-                check_block(if_.then(), result_type, Some(if_.type_()), variables)?;
-                check_block(if_.else_(), result_type, Some(if_.type_()), variables)?;
+            check_equality(
+                &call.type_().clone().into(),
+                &check_expression(call.function(), variables)?,
+            )?;
+
+            for (argument, type_) in call.arguments().iter().zip(call.type_().arguments()) {
+                check_equality(&check_expression(argument, variables)?, type_)?;
             }
-            Instruction::Load(load) => {
-                check_equality(
-                    &check_expression(load.pointer(), variables)?,
-                    &types::Pointer::new(load.type_().clone()).into(),
-                )?;
+        }
+        Instruction::CompareAndSwap(cas) => {
+            if !is_valid_failure_ordering(cas.success_ordering(), cas.failure_ordering()) {
+                return Err(TypeCheckError::InvalidAtomicOrdering(instruction.clone()));
             }
-            Instruction::MemoryCopy(copy) => {
-                let pointer_type = types::Pointer::new(types::Primitive::Integer8).into();
 
-                check_equality(&check_expression(copy.source(), variables)?, &pointer_type)?;
-                check_equality(
-                    &check_expression(copy.destination(), variables)?,
-                    &pointer_type,
-                )?;
-                check_equality(
-                    &check_expression(copy.size(), variables)?,
-                    &types::Primitive::PointerInteger.into(),
-                )?;
-            }
-            Instruction::ReallocateHeap(reallocate) => {
-                check_equality(
-                    &check_expression(reallocate.pointer(), variables)?,
-                    &generic_pointer_type(),
-                )?;
+            check_equality(
+                &check_expression(cas.pointer(), variables)?,
+                &types::Pointer::new(cas.type_().clone()).into(),
+            )?;
 
-                check_equality(
-                    &check_expression(reallocate.size(), variables)?,
-                    &types::Primitive::PointerInteger.into(),
-                )?;
-            }
-            Instruction::Store(store) => {
-                check_equality(&check_expression(store.value(), variables)?, store.type_())?;
-                check_equality(
-                    &check_expression(store.pointer(), variables)?,
-                    &types::Pointer::new(store.type_().clone()).into(),
-                )?;
+            check_equality(
+                &check_expression(cas.old_value(), variables)?,
+                &cas.type_().clone(),
+            )?;
+
+            check_equality(
+                &check_expression(cas.new_value(), variables)?,
+                &cas.type_().clone(),
+            )?;
+        }
+        Instruction::DeconstructRecord(deconstruct) => {
+            check_equality(
+                &check_expression(deconstruct.record(), variables)?,
+                &deconstruct.type_().clone().into(),
+            )?;
+
+            check_record_index(deconstruct.field_index(), deconstruct.type_())?;
+        }
+        Instruction::DeconstructUnion(deconstruct) => {
+            check_equality(
+                &check_expression(deconstruct.union(), variables)?,
+                &deconstruct.type_().clone().into(),
+            )?;
+
+            check_union_index(deconstruct.member_index(), deconstruct.type_())?;
+        }
+        Instruction::Fence(_) => {}
+        Instruction::FreeHeap(free) => {
+            check_equality(
+                &check_expression(free.pointer(), variables)?,
+                &generic_pointer_type(),
+            )?;
+        }
+        Instruction::If(if_) => {
+            check_equality(
+                &check_expression(if_.condition(), variables)?,
+                &types::Primitive::Boolean.into(),
+            )?;
+        }
+        Instruction::Load(load) => {
+            if let Some(alignment) = load.alignment() {
+                if !alignment.is_power_of_two() {
+                    return Err(TypeCheckError::InvalidAlignment(alignment));
+                }
             }
+
+            check_equality(
+                &check_expression(load.pointer(), variables)?,
+                &types::Pointer::new(load.type_().clone()).into(),
+            )?;
         }
-    }
+        Instruction::MemoryCopy(copy) => {
+            let pointer_type = types::Pointer::new(types::Primitive::Integer8).into();
 
-    match block.terminal_instruction() {
-        TerminalInstruction::Branch(branch) => {
-            let branch_type =
-                branch_type.ok_or_else(|| TypeCheckError::InvalidBranch(branch.clone()))?;
+            check_equality(&check_expression(copy.source(), variables)?, &pointer_type)?;
+            check_equality(
+                &check_expression(copy.destination(), variables)?,
+                &pointer_type,
+            )?;
+            check_equality(
+                &check_expression(copy.size(), variables)?,
+                &types::Primitive::PointerInteger.into(),
+            )?;
+        }
+        Instruction::ReallocateHeap(reallocate) => {
+            check_equality(
+                &check_expression(reallocate.pointer(), variables)?,
+                &generic_pointer_type(),
+            )?;
 
-            check_equality(branch.type_(), branch_type)?;
             check_equality(
-                &check_expression(branch.expression(), variables)?,
-                branch_type,
+                &check_expression(reallocate.size(), variables)?,
+                &types::Primitive::PointerInteger.into(),
             )?;
         }
-        TerminalInstruction::Return(return_) => {
-            check_equality(return_.type_(), result_type)?;
+        Instruction::Store(store) => {
+            check_equality(&check_expression(store.value(), variables)?, store.type_())?;
             check_equality(
-                &check_expression(return_.expression(), variables)?,
-                result_type,
+                &check_expression(store.pointer(), variables)?,
+                &types::Pointer::new(store.type_().clone()).into(),
             )?;
         }
-        TerminalInstruction::Unreachable => {}
     }
 
     Ok(())
@@ -426,6 +500,21 @@ fn check_equality(one: &Type, other: &Type) -> Result<(), TypeCheckError> {
     }
 }
 
+// Failure orderings cannot release values nor be stronger than success ones.
+fn is_valid_failure_ordering(success: AtomicOrdering, failure: AtomicOrdering) -> bool {
+    match failure {
+        AtomicOrdering::Relaxed => true,
+        AtomicOrdering::Acquire => matches!(
+            success,
+            AtomicOrdering::Acquire
+                | AtomicOrdering::AcquireRelease
+                | AtomicOrdering::SequentiallyConsistent
+        ),
+        AtomicOrdering::SequentiallyConsistent => success == AtomicOrdering::SequentiallyConsistent,
+        AtomicOrdering::Release | AtomicOrdering::AcquireRelease => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -435,6 +524,10 @@ mod tests {
         types::Function::new(arguments, result, CallingConvention::Source)
     }
 
+    fn create_instruction_error(error: TypeCheckError) -> TypeCheckError {
+        TypeCheckError::Instruction(InstructionPath::new("f", vec![], 0), error.into())
+    }
+
     fn create_function_definition(
         name: impl Into<String>,
         arguments: Vec<Argument>,
@@ -542,6 +635,203 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn check_vtable_of_functions() -> Result<(), TypeCheckError> {
+        let function_type = create_function_type(
+            vec![types::Primitive::PointerInteger.into()],
+            types::Primitive::PointerInteger,
+        );
+        let vtable_type = types::Record::new(vec![
+            function_type.clone().into(),
+            function_type.clone().into(),
+        ]);
+
+        check(&Module::new(
+            vec![],
+            vec![FunctionDeclaration::new("f", function_type.clone())],
+            vec![VariableDefinition::new(
+                "vtable",
+                Record::new(
+                    vtable_type.clone(),
+                    vec![Variable::new("f").into(), Variable::new("g").into()],
+                ),
+                vtable_type.clone(),
+                Default::default(),
+            )],
+            vec![create_function_definition(
+                "g",
+                vec![Argument::new("x", types::Primitive::PointerInteger)],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    vec![
+                        Load::new(
+                            function_type.clone(),
+                            RecordAddress::new(vtable_type, Variable::new("vtable"), 0),
+                            "h",
+                        )
+                        .into(),
+                        Call::new(
+                            function_type,
+                            Variable::new("h"),
+                            vec![Variable::new("x").into()],
+                            "y",
+                        )
+                        .into(),
+                    ],
+                    Return::new(types::Primitive::PointerInteger, Variable::new("y")),
+                ),
+            )],
+        ))
+    }
+
+    #[test]
+    fn fail_to_check_function_alignment() {
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![FunctionDefinition::new(
+                    "f",
+                    vec![],
+                    types::void_type(),
+                    Block::new(vec![], Return::new(types::void_type(), void_value())),
+                    FunctionDefinitionOptions::new().set_alignment(Some(48)),
+                )],
+            )),
+            Err(TypeCheckError::InvalidAlignment(48))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_load_alignment() {
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![Argument::new(
+                        "x",
+                        types::Pointer::new(types::Primitive::PointerInteger),
+                    )],
+                    types::void_type(),
+                    Block::new(
+                        vec![
+                            Load::new(types::Primitive::PointerInteger, Variable::new("x"), "y")
+                                .set_alignment(Some(12))
+                                .into(),
+                        ],
+                        Return::new(types::void_type(), void_value()),
+                    ),
+                )],
+            )),
+            Err(create_instruction_error(TypeCheckError::InvalidAlignment(
+                12
+            )))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_load_alignment_in_if() {
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![Argument::new(
+                        "x",
+                        types::Pointer::new(types::Primitive::PointerInteger),
+                    )],
+                    types::void_type(),
+                    Block::new(
+                        vec![If::new(
+                            types::void_type(),
+                            Primitive::Boolean(true),
+                            Block::new(vec![], Branch::new(types::void_type(), void_value())),
+                            Block::new(
+                                vec![
+                                    AllocateStack::new(types::Primitive::PointerInteger, "y")
+                                        .into(),
+                                    Load::new(
+                                        types::Primitive::PointerInteger,
+                                        Variable::new("x"),
+                                        "z",
+                                    )
+                                    .set_alignment(Some(12))
+                                    .into(),
+                                ],
+                                Branch::new(types::void_type(), void_value()),
+                            ),
+                            "w",
+                        )
+                        .into()],
+                        Return::new(types::void_type(), void_value()),
+                    ),
+                )],
+            )),
+            Err(TypeCheckError::Instruction(
+                InstructionPath::new("f", vec![(0, IfBranch::Else)], 1),
+                TypeCheckError::InvalidAlignment(12).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_deconstruct_record_with_out_of_range_index() {
+        let record_type = types::Record::new(vec![types::Primitive::PointerInteger.into()]);
+
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![Argument::new("x", record_type.clone())],
+                    types::void_type(),
+                    Block::new(
+                        vec![
+                            DeconstructRecord::new(record_type, Variable::new("x"), 1, "y").into()
+                        ],
+                        Return::new(types::void_type(), void_value()),
+                    ),
+                )],
+            )),
+            Err(create_instruction_error(TypeCheckError::IndexOutOfRange))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_record_address_with_out_of_range_index() {
+        let record_type = types::Record::new(vec![types::Primitive::PointerInteger.into()]);
+        let pointer_type = types::Pointer::new(types::Primitive::PointerInteger);
+
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![Argument::new("x", types::Pointer::new(record_type.clone()))],
+                    pointer_type.clone(),
+                    Block::new(
+                        vec![],
+                        Return::new(
+                            pointer_type,
+                            RecordAddress::new(record_type, Variable::new("x"), 1),
+                        ),
+                    ),
+                )],
+            )),
+            Err(TypeCheckError::IndexOutOfRange)
+        );
+    }
+
     #[test]
     fn check_return() -> Result<(), TypeCheckError> {
         check(&Module::new(
@@ -686,6 +976,37 @@ mod tests {
         .unwrap()
     }
 
+    #[test]
+    fn fail_to_check_call_with_wrong_calling_convention() {
+        let function_type = types::Function::new(
+            vec![],
+            types::Primitive::PointerInteger,
+            CallingConvention::Target,
+        );
+        let call_type = create_function_type(vec![], types::Primitive::PointerInteger);
+
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![FunctionDeclaration::new("g", function_type.clone())],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    Block::new(
+                        vec![Call::new(call_type.clone(), Variable::new("g"), vec![], "x").into()],
+                        Return::new(types::Primitive::PointerInteger, Variable::new("x")),
+                    ),
+                )],
+            )),
+            Err(create_instruction_error(TypeCheckError::TypesNotMatched(
+                call_type.into(),
+                function_type.into()
+            )))
+        );
+    }
+
     #[test]
     fn check_if() -> Result<(), TypeCheckError> {
         check(&Module::new(
@@ -717,6 +1038,37 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn fail_to_check_if_with_float_condition() {
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![],
+                    types::void_type(),
+                    Block::new(
+                        vec![If::new(
+                            types::void_type(),
+                            Primitive::Float64(1.0),
+                            Block::new(vec![], Branch::new(types::void_type(), void_value())),
+                            Block::new(vec![], Branch::new(types::void_type(), void_value())),
+                            "x",
+                        )
+                        .into()],
+                        Return::new(types::void_type(), void_value()),
+                    ),
+                )],
+            )),
+            Err(create_instruction_error(TypeCheckError::TypesNotMatched(
+                types::Primitive::Float64.into(),
+                types::Primitive::Boolean.into(),
+            )))
+        );
+    }
+
     #[test]
     fn check_load() -> Result<(), TypeCheckError> {
         check(&Module::new(
@@ -852,6 +1204,194 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn check_atomic_operation_on_integer8() -> Result<(), TypeCheckError> {
+        check(&Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![create_function_definition(
+                "f",
+                vec![Argument::new(
+                    "x",
+                    types::Pointer::new(types::Primitive::Integer8),
+                )],
+                types::Primitive::Integer8,
+                Block::new(
+                    vec![AtomicOperation::new(
+                        types::Primitive::Integer8,
+                        AtomicOperator::Add,
+                        Variable::new("x"),
+                        Primitive::Integer8(1),
+                        AtomicOrdering::Relaxed,
+                        "y",
+                    )
+                    .into()],
+                    Return::new(types::Primitive::Integer8, Variable::new("y")),
+                ),
+            )],
+        ))
+    }
+
+    #[test]
+    fn fail_to_check_atomic_operation_with_mismatched_width() {
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![Argument::new(
+                        "x",
+                        types::Pointer::new(types::Primitive::Integer32),
+                    )],
+                    types::Primitive::Integer8,
+                    Block::new(
+                        vec![AtomicOperation::new(
+                            types::Primitive::Integer8,
+                            AtomicOperator::Add,
+                            Variable::new("x"),
+                            Primitive::Integer8(1),
+                            AtomicOrdering::Relaxed,
+                            "y",
+                        )
+                        .into()],
+                        Return::new(types::Primitive::Integer8, Variable::new("y")),
+                    ),
+                )],
+            )),
+            Err(create_instruction_error(TypeCheckError::TypesNotMatched(
+                types::Pointer::new(types::Primitive::Integer32).into(),
+                types::Pointer::new(types::Primitive::Integer8).into(),
+            )))
+        );
+    }
+
+    mod atomic_ordering {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn check_instruction(instruction: impl Into<Instruction>) -> Result<(), TypeCheckError> {
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![create_function_definition(
+                    "f",
+                    vec![Argument::new(
+                        "x",
+                        types::Pointer::new(types::Primitive::PointerInteger),
+                    )],
+                    types::void_type(),
+                    Block::new(
+                        vec![instruction.into()],
+                        Return::new(types::void_type(), void_value()),
+                    ),
+                )],
+            ))
+        }
+
+        fn create_compare_and_swap(
+            success_ordering: AtomicOrdering,
+            failure_ordering: AtomicOrdering,
+        ) -> CompareAndSwap {
+            CompareAndSwap::new(
+                types::Primitive::PointerInteger,
+                Variable::new("x"),
+                Primitive::PointerInteger(0),
+                Primitive::PointerInteger(1),
+                success_ordering,
+                failure_ordering,
+                "y",
+            )
+        }
+
+        #[test]
+        fn fail_to_check_load_with_release_ordering() {
+            for ordering in [AtomicOrdering::Release, AtomicOrdering::AcquireRelease] {
+                let load = AtomicLoad::new(
+                    types::Primitive::PointerInteger,
+                    Variable::new("x"),
+                    ordering,
+                    "y",
+                );
+
+                assert_eq!(
+                    check_instruction(load.clone()),
+                    Err(create_instruction_error(
+                        TypeCheckError::InvalidAtomicOrdering(load.into())
+                    ))
+                );
+            }
+        }
+
+        #[test]
+        fn fail_to_check_store_with_acquire_ordering() {
+            for ordering in [AtomicOrdering::Acquire, AtomicOrdering::AcquireRelease] {
+                let store = AtomicStore::new(
+                    types::Primitive::PointerInteger,
+                    Primitive::PointerInteger(42),
+                    Variable::new("x"),
+                    ordering,
+                );
+
+                assert_eq!(
+                    check_instruction(store.clone()),
+                    Err(create_instruction_error(
+                        TypeCheckError::InvalidAtomicOrdering(store.into())
+                    ))
+                );
+            }
+        }
+
+        #[test]
+        fn fail_to_check_compare_and_swap_with_stronger_failure_ordering() {
+            for (success_ordering, failure_ordering) in [
+                (AtomicOrdering::Relaxed, AtomicOrdering::Acquire),
+                (AtomicOrdering::Release, AtomicOrdering::Acquire),
+                (
+                    AtomicOrdering::AcquireRelease,
+                    AtomicOrdering::SequentiallyConsistent,
+                ),
+                (
+                    AtomicOrdering::SequentiallyConsistent,
+                    AtomicOrdering::Release,
+                ),
+                (
+                    AtomicOrdering::SequentiallyConsistent,
+                    AtomicOrdering::AcquireRelease,
+                ),
+            ] {
+                let cas = create_compare_and_swap(success_ordering, failure_ordering);
+
+                assert_eq!(
+                    check_instruction(cas.clone()),
+                    Err(create_instruction_error(
+                        TypeCheckError::InvalidAtomicOrdering(cas.into())
+                    ))
+                );
+            }
+        }
+
+        #[test]
+        fn check_compare_and_swap_with_valid_orderings() {
+            for (success_ordering, failure_ordering) in [
+                (AtomicOrdering::Relaxed, AtomicOrdering::Relaxed),
+                (AtomicOrdering::AcquireRelease, AtomicOrdering::Acquire),
+                (
+                    AtomicOrdering::SequentiallyConsistent,
+                    AtomicOrdering::SequentiallyConsistent,
+                ),
+            ] {
+                assert_eq!(
+                    check_instruction(create_compare_and_swap(success_ordering, failure_ordering)),
+                    Ok(())
+                );
+            }
+        }
+    }
+
     #[test]
     fn check_pointer_address() -> Result<(), TypeCheckError> {
         let pointer_type = types::Pointer::new(types::Primitive::PointerInteger);
@@ -8,6 +8,9 @@ use std::{
 pub enum TypeCheckError {
     FunctionArguments(Call),
     IndexOutOfRange,
+    Instruction(InstructionPath, Box<TypeCheckError>),
+    InvalidAlignment(usize),
+    InvalidAtomicOrdering(Instruction),
     InvalidBranch(Branch),
     RecordFields(Record),
     TypesNotMatched(Type, Type),
@@ -146,6 +146,10 @@ fn collect_from_instruction(instruction: &Instruction, types: &mut FnvHashSet<Ty
     match instruction {
         Instruction::AllocateHeap(allocate) => collect_from_expression(allocate.size()),
         Instruction::AllocateStack(allocate) => {
+            if let Some(count) = allocate.count() {
+                collect_from_expression(count);
+            }
+
             types.insert(allocate.type_().clone());
         }
         Instruction::AtomicLoad(load) => {
@@ -192,7 +196,13 @@ fn collect_from_instruction(instruction: &Instruction, types: &mut FnvHashSet<Ty
             types.insert(deconstruct.type_().clone().into());
         }
         Instruction::Fence(_) => {}
-        Instruction::FreeHeap(free) => collect_from_expression(free.pointer()),
+        Instruction::FreeHeap(free) => {
+            collect_from_expression(free.pointer());
+
+            if let Some(type_) = free.type_() {
+                types.insert(type_.clone());
+            }
+        }
         Instruction::If(if_) => {
             collect_from_expression(if_.condition());
             collect_from_block(if_.then(), types);
@@ -293,6 +303,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sort_record_of_pointers() {
+        let pointer_type = types::Pointer::new(types::Primitive::Integer64);
+        let record_type = types::Record::new(vec![
+            pointer_type.clone().into(),
+            types::Pointer::new(pointer_type.clone()).into(),
+        ]);
+
+        let types = collect(&Module::new(
+            vec![VariableDeclaration::new("x", record_type.clone())],
+            vec![],
+            vec![],
+            vec![],
+        ));
+        let position = |type_: Type| types.iter().position(|other| other == &type_).unwrap();
+
+        assert_eq!(types.len(), 4);
+        assert!(
+            position(types::Primitive::Integer64.into()) < position(pointer_type.clone().into())
+        );
+        assert!(
+            position(pointer_type.clone().into())
+                < position(types::Pointer::new(pointer_type.clone()).into())
+        );
+        assert!(position(pointer_type.clone().into()) < position(record_type.clone().into()));
+        assert!(position(types::Pointer::new(pointer_type).into()) < position(record_type.into()));
+    }
+
     #[test]
     fn collect_from_nested_function_types() {
         assert_eq!(
@@ -100,6 +100,10 @@ fn convert_instruction(
         }
         Instruction::AllocateStack(allocate) => {
             *allocate.type_mut() = convert(allocate.type_());
+
+            if let Some(count) = allocate.count_mut() {
+                *count = convert_expression(count, convert)?;
+            }
         }
         Instruction::AtomicLoad(load) => {
             *load.type_mut() = convert(load.type_());
@@ -152,6 +156,10 @@ fn convert_instruction(
         Instruction::Fence(_) => {}
         Instruction::FreeHeap(free) => {
             *free.pointer_mut() = convert_expression(free.pointer(), convert)?;
+
+            if let Some(type_) = free.type_mut() {
+                *type_ = convert(type_);
+            }
         }
         Instruction::If(if_) => {
             *if_.type_mut() = convert(if_.type_());
@@ -1,25 +1,48 @@
-use crate::types::{self, Type};
+mod layout_cache;
+
+pub use self::layout_cache::LayoutCache;
+use crate::types::Type;
 
 /// Returns a size of a type in bytes.
 ///
 /// Based on: https://refspecs.linuxfoundation.org/elf/x86_64-SysV-psABI.pdf
 pub fn calculate_size(type_: &Type, word_bytes: usize) -> usize {
+    calculate_size_with(
+        type_,
+        word_bytes,
+        &|type_| calculate_size(type_, word_bytes),
+        &|type_| calculate_alignment(type_, word_bytes),
+    )
+}
+
+/// Returns an alignment of a type in bytes.
+pub fn calculate_alignment(type_: &Type, word_bytes: usize) -> usize {
+    calculate_alignment_with(
+        type_,
+        word_bytes,
+        &|type_| calculate_size(type_, word_bytes),
+        &|type_| calculate_alignment(type_, word_bytes),
+    )
+}
+
+// Sizes and alignments of inner types are calculated by given functions.
+fn calculate_size_with(
+    type_: &Type,
+    word_bytes: usize,
+    calculate_size: &impl Fn(&Type) -> usize,
+    calculate_alignment: &impl Fn(&Type) -> usize,
+) -> usize {
     match type_ {
-        Type::Primitive(primitive) => match primitive {
-            types::Primitive::Boolean | types::Primitive::Integer8 => 1,
-            types::Primitive::Float32 | types::Primitive::Integer32 => 4,
-            types::Primitive::Float64 | types::Primitive::Integer64 => 8,
-            types::Primitive::PointerInteger => word_bytes,
-        },
+        Type::Primitive(primitive) => primitive.byte_width(word_bytes),
         Type::Record(record) => {
             let mut size = 0;
 
             for field in record.fields() {
-                let alignment = calculate_alignment(field, word_bytes);
+                let alignment = calculate_alignment(field);
 
                 size = size.max((size as f64 / alignment as f64).ceil() as usize * alignment);
 
-                size += calculate_size(field, word_bytes);
+                size += calculate_size(field);
             }
 
             size
@@ -27,30 +50,34 @@ pub fn calculate_size(type_: &Type, word_bytes: usize) -> usize {
         Type::Union(union) => union
             .members()
             .iter()
-            .map(|type_| calculate_size(type_, word_bytes))
+            .map(calculate_size)
             .max()
             .unwrap_or_default(),
         Type::Function(_) | Type::Pointer(_) => word_bytes,
     }
 }
 
-/// Returns an alignment of a type in bytes.
-pub fn calculate_alignment(type_: &Type, word_bytes: usize) -> usize {
+fn calculate_alignment_with(
+    type_: &Type,
+    word_bytes: usize,
+    calculate_size: &impl Fn(&Type) -> usize,
+    calculate_alignment: &impl Fn(&Type) -> usize,
+) -> usize {
     match type_ {
         Type::Record(record) => record
             .fields()
             .iter()
-            .map(|type_| calculate_alignment(type_, word_bytes))
+            .map(calculate_alignment)
             .max()
             .unwrap_or_default(),
         Type::Union(union) => union
             .members()
             .iter()
-            .map(|type_| calculate_alignment(type_, word_bytes))
+            .map(calculate_alignment)
             .max()
             .unwrap_or_default(),
         Type::Function(_) | Type::Pointer(_) | Type::Primitive(_) => {
-            calculate_size(type_, word_bytes)
+            calculate_size_with(type_, word_bytes, calculate_size, calculate_alignment)
         }
     }
 }
@@ -58,6 +85,7 @@ pub fn calculate_alignment(type_: &Type, word_bytes: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types;
 
     mod size {
         use super::*;
@@ -103,6 +131,21 @@ mod tests {
             );
         }
 
+        #[test]
+        fn union_with_record() {
+            assert_eq!(
+                calculate_size(
+                    &types::Union::new(vec![
+                        types::Primitive::Integer8.into(),
+                        types::Record::new(vec![types::Primitive::Integer32.into(); 3]).into(),
+                    ])
+                    .into(),
+                    8
+                ),
+                12
+            );
+        }
+
         #[test]
         fn empty_record() {
             assert_eq!(calculate_size(&types::Record::new(vec![]).into(), 8), 0);
@@ -254,6 +297,25 @@ mod tests {
                 9
             );
         }
+
+        #[test]
+        fn nested_record_across_words() {
+            assert_eq!(
+                calculate_size(
+                    &types::Record::new(vec![
+                        types::Primitive::Integer32.into(),
+                        types::Record::new(vec![
+                            types::Primitive::Integer32.into(),
+                            types::Primitive::Integer32.into(),
+                        ])
+                        .into(),
+                    ])
+                    .into(),
+                    8
+                ),
+                12
+            );
+        }
     }
 
     mod alignment {
@@ -300,6 +362,21 @@ mod tests {
             );
         }
 
+        #[test]
+        fn union_with_float_64() {
+            assert_eq!(
+                calculate_alignment(
+                    &types::Union::new(vec![
+                        types::Primitive::Integer8.into(),
+                        types::Primitive::Float64.into()
+                    ])
+                    .into(),
+                    4
+                ),
+                8
+            );
+        }
+
         #[test]
         fn empty_record() {
             assert_eq!(
@@ -0,0 +1,128 @@
+use super::{calculate_alignment_with, calculate_size_with};
+use crate::types::Type;
+use fnv::FnvHashMap;
+use std::cell::RefCell;
+
+// Memoizes sizes and alignments of types including ones of their inner types.
+#[derive(Debug, Default)]
+pub struct LayoutCache {
+    sizes: RefCell<FnvHashMap<(Type, usize), usize>>,
+    alignments: RefCell<FnvHashMap<(Type, usize), usize>>,
+}
+
+impl LayoutCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn size(&self, type_: &Type, word_bytes: usize) -> usize {
+        if let Some(&size) = self.sizes.borrow().get(&(type_.clone(), word_bytes)) {
+            return size;
+        }
+
+        let size = calculate_size_with(
+            type_,
+            word_bytes,
+            &|type_| self.size(type_, word_bytes),
+            &|type_| self.alignment(type_, word_bytes),
+        );
+
+        self.sizes
+            .borrow_mut()
+            .insert((type_.clone(), word_bytes), size);
+
+        size
+    }
+
+    pub fn alignment(&self, type_: &Type, word_bytes: usize) -> usize {
+        if let Some(&alignment) = self.alignments.borrow().get(&(type_.clone(), word_bytes)) {
+            return alignment;
+        }
+
+        let alignment = calculate_alignment_with(
+            type_,
+            word_bytes,
+            &|type_| self.size(type_, word_bytes),
+            &|type_| self.alignment(type_, word_bytes),
+        );
+
+        self.alignments
+            .borrow_mut()
+            .insert((type_.clone(), word_bytes), alignment);
+
+        alignment
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.borrow().len() + self.alignments.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::type_size::{calculate_alignment, calculate_size},
+        types,
+    };
+
+    fn create_types() -> Vec<Type> {
+        let record = types::Record::new(vec![
+            types::Primitive::Integer8.into(),
+            types::Primitive::Float64.into(),
+            types::Primitive::PointerInteger.into(),
+        ]);
+
+        vec![
+            types::Primitive::Boolean.into(),
+            types::Primitive::PointerInteger.into(),
+            types::Pointer::new(types::Primitive::Integer8).into(),
+            record.clone().into(),
+            types::Record::new(vec![
+                record.clone().into(),
+                types::Primitive::Integer32.into(),
+            ])
+            .into(),
+            types::Union::new(vec![record.into(), types::Primitive::Integer8.into()]).into(),
+            types::Record::new(vec![]).into(),
+        ]
+    }
+
+    #[test]
+    fn agree_with_uncached_calculation() {
+        let cache = LayoutCache::new();
+
+        for word_bytes in [4, 8] {
+            for type_ in create_types() {
+                for _ in 0..2 {
+                    assert_eq!(
+                        cache.size(&type_, word_bytes),
+                        calculate_size(&type_, word_bytes)
+                    );
+                    assert_eq!(
+                        cache.alignment(&type_, word_bytes),
+                        calculate_alignment(&type_, word_bytes)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cache_inner_types() {
+        let cache = LayoutCache::new();
+        let inner = types::Record::new(vec![types::Primitive::Integer64.into()]);
+
+        cache.size(&types::Record::new(vec![inner.clone().into()]).into(), 8);
+
+        let len = cache.len();
+
+        cache.size(&inner.into(), 8);
+
+        assert_eq!(cache.len(), len);
+    }
+}
@@ -0,0 +1,407 @@
+use super::expression_conversion;
+use crate::{ir::*, types};
+use fnv::FnvHashMap;
+use std::mem::take;
+
+// Replace pure operations with undefined operands by undefined values of their
+// result types. Instructions with side effects are kept as they are.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_block(definition.body_mut(), &mut Default::default());
+    }
+}
+
+fn transform_block(block: &mut Block, undefined_values: &mut FnvHashMap<String, Expression>) {
+    let mut instructions = Vec::with_capacity(block.instructions().len());
+
+    for mut instruction in take(block.instructions_mut()) {
+        expression_conversion::convert_in_instruction(&mut instruction, &|expression| {
+            propagate(expression, undefined_values)
+        });
+
+        match &mut instruction {
+            Instruction::DeconstructRecord(deconstruct) => {
+                if let (Expression::Undefined(_), Some(type_)) = (
+                    deconstruct.record(),
+                    deconstruct.type_().fields().get(deconstruct.field_index()),
+                ) {
+                    undefined_values.insert(
+                        deconstruct.name().into(),
+                        Undefined::new(type_.clone()).into(),
+                    );
+                    continue;
+                }
+            }
+            Instruction::DeconstructUnion(deconstruct) => {
+                if let (Expression::Undefined(_), Some(type_)) = (
+                    deconstruct.union(),
+                    deconstruct
+                        .type_()
+                        .members()
+                        .get(deconstruct.member_index()),
+                ) {
+                    undefined_values.insert(
+                        deconstruct.name().into(),
+                        Undefined::new(type_.clone()).into(),
+                    );
+                    continue;
+                }
+            }
+            Instruction::If(if_) => {
+                transform_block(if_.then_mut(), undefined_values);
+                transform_block(if_.else_mut(), undefined_values);
+            }
+            _ => {}
+        }
+
+        instructions.push(instruction);
+    }
+
+    *block.instructions_mut() = instructions;
+
+    expression_conversion::convert_in_terminal_instruction(
+        block.terminal_instruction_mut(),
+        &|expression| propagate(expression, undefined_values),
+    );
+}
+
+// Operands are already converted because expressions are converted bottom-up.
+fn propagate(
+    expression: &Expression,
+    undefined_values: &FnvHashMap<String, Expression>,
+) -> Expression {
+    match expression {
+        Expression::ArithmeticOperation(operation)
+            if is_undefined(operation.lhs()) || is_undefined(operation.rhs()) =>
+        {
+            propagate_arithmetic_operation(operation).unwrap_or_else(|| expression.clone())
+        }
+        Expression::BitCast(bit_cast) if is_undefined(bit_cast.expression()) => {
+            Undefined::new(bit_cast.to().clone()).into()
+        }
+        Expression::BitwiseNotOperation(operation) if is_undefined(operation.value()) => {
+            Undefined::new(operation.type_()).into()
+        }
+        Expression::BitwiseOperation(operation)
+            if is_undefined(operation.lhs()) || is_undefined(operation.rhs()) =>
+        {
+            propagate_bitwise_operation(operation).unwrap_or_else(|| expression.clone())
+        }
+        Expression::ComparisonOperation(operation)
+            if is_undefined(operation.lhs()) || is_undefined(operation.rhs()) =>
+        {
+            Undefined::new(types::Primitive::Boolean).into()
+        }
+        Expression::Variable(variable) => undefined_values
+            .get(variable.name())
+            .cloned()
+            .unwrap_or_else(|| expression.clone()),
+        _ => expression.clone(),
+    }
+}
+
+// Operations are kept if their results cannot be arbitrary values for some
+// values of defined operands. Absorbing constants are folded.
+fn propagate_arithmetic_operation(operation: &ArithmeticOperation) -> Option<Expression> {
+    let undefined = Undefined::new(operation.type_()).into();
+
+    match operation.operator() {
+        ArithmeticOperator::Add | ArithmeticOperator::Subtract => Some(undefined),
+        ArithmeticOperator::Multiply => {
+            if let Some(zero) = [operation.lhs(), operation.rhs()]
+                .into_iter()
+                .find(|expression| is_zero(expression))
+            {
+                Some(zero.clone())
+            } else if operation.type_().is_float()
+                && !(is_undefined(operation.lhs()) && is_undefined(operation.rhs()))
+            {
+                None
+            } else {
+                Some(undefined)
+            }
+        }
+        ArithmeticOperator::Divide => is_undefined(operation.rhs()).then_some(undefined),
+    }
+}
+
+fn propagate_bitwise_operation(operation: &BitwiseOperation) -> Option<Expression> {
+    let absorbing = |expression: &Expression| match operation.operator() {
+        BitwiseOperator::And => is_zero(expression),
+        BitwiseOperator::Or => is_all_ones(expression),
+        _ => false,
+    };
+
+    if let Some(constant) = [operation.lhs(), operation.rhs()]
+        .into_iter()
+        .find(|expression| absorbing(expression))
+    {
+        Some(constant.clone())
+    } else if matches!(
+        operation.operator(),
+        BitwiseOperator::LeftShift | BitwiseOperator::RightShift(_)
+    ) && !is_undefined(operation.rhs())
+    {
+        None
+    } else {
+        Some(Undefined::new(operation.type_()).into())
+    }
+}
+
+fn is_undefined(expression: &Expression) -> bool {
+    matches!(expression, Expression::Undefined(_))
+}
+
+fn is_zero(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Primitive(
+            Primitive::Boolean(false)
+                | Primitive::Integer8(0)
+                | Primitive::Integer32(0)
+                | Primitive::Integer64(0)
+                | Primitive::PointerInteger(0)
+        )
+    )
+}
+
+fn is_all_ones(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Primitive(
+            Primitive::Boolean(true)
+                | Primitive::Integer8(u8::MAX)
+                | Primitive::Integer32(u32::MAX)
+                | Primitive::Integer64(u64::MAX)
+                | Primitive::PointerInteger(-1)
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analysis::type_check, types::void_type};
+    use pretty_assertions::assert_eq;
+
+    fn create_module(instructions: Vec<Instruction>, result: impl Into<Expression>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new(
+                    "p",
+                    types::Pointer::new(types::Primitive::PointerInteger),
+                )],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    instructions,
+                    Return::new(types::Primitive::PointerInteger, result),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn collapse_arithmetic_chain() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![],
+                ArithmeticOperation::new(
+                    types::Primitive::PointerInteger,
+                    ArithmeticOperator::Multiply,
+                    ArithmeticOperation::new(
+                        types::Primitive::PointerInteger,
+                        ArithmeticOperator::Add,
+                        Undefined::new(types::Primitive::PointerInteger),
+                        Primitive::PointerInteger(1),
+                    ),
+                    Primitive::PointerInteger(2),
+                ),
+            )),
+            create_module(vec![], Undefined::new(types::Primitive::PointerInteger))
+        );
+    }
+
+    #[test]
+    fn fold_multiplication_by_zero() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![],
+                ArithmeticOperation::new(
+                    types::Primitive::PointerInteger,
+                    ArithmeticOperator::Multiply,
+                    Undefined::new(types::Primitive::PointerInteger),
+                    Primitive::PointerInteger(0),
+                ),
+            )),
+            create_module(vec![], Primitive::PointerInteger(0))
+        );
+    }
+
+    #[test]
+    fn fold_bitwise_and_with_zero() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![],
+                BitwiseOperation::new(
+                    types::Primitive::PointerInteger,
+                    BitwiseOperator::And,
+                    Primitive::PointerInteger(0),
+                    Undefined::new(types::Primitive::PointerInteger),
+                ),
+            )),
+            create_module(vec![], Primitive::PointerInteger(0))
+        );
+    }
+
+    #[test]
+    fn fold_bitwise_or_with_all_ones() {
+        assert_eq!(
+            transform_module(create_module(
+                vec![],
+                BitwiseOperation::new(
+                    types::Primitive::PointerInteger,
+                    BitwiseOperator::Or,
+                    Undefined::new(types::Primitive::PointerInteger),
+                    Primitive::PointerInteger(-1),
+                ),
+            )),
+            create_module(vec![], Primitive::PointerInteger(-1))
+        );
+    }
+
+    #[test]
+    fn keep_division_of_undefined_value() {
+        let module = create_module(
+            vec![],
+            ArithmeticOperation::new(
+                types::Primitive::PointerInteger,
+                ArithmeticOperator::Divide,
+                Undefined::new(types::Primitive::PointerInteger),
+                Primitive::PointerInteger(2),
+            ),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+
+    #[test]
+    fn propagate_through_deconstruct_record() {
+        let record_type = types::Record::new(vec![types::Primitive::PointerInteger.into()]);
+
+        assert_eq!(
+            transform_module(create_module(
+                vec![DeconstructRecord::new(
+                    record_type.clone(),
+                    Undefined::new(record_type),
+                    0,
+                    "x"
+                )
+                .into()],
+                Variable::new("x"),
+            )),
+            create_module(vec![], Undefined::new(types::Primitive::PointerInteger))
+        );
+    }
+
+    #[test]
+    fn keep_deconstruct_record_with_out_of_range_index() {
+        let record_type = types::Record::new(vec![types::Primitive::PointerInteger.into()]);
+        let mut module = create_module(
+            vec![
+                DeconstructRecord::new(record_type.clone(), Undefined::new(record_type), 1, "x")
+                    .into(),
+            ],
+            Variable::new("x"),
+        );
+        let original = module.clone();
+
+        transform(&mut module);
+
+        assert_eq!(module, original);
+    }
+
+    #[test]
+    fn keep_deconstruct_union_with_out_of_range_index() {
+        let union_type = types::Union::new(vec![types::Primitive::PointerInteger.into()]);
+        let mut module = create_module(
+            vec![
+                DeconstructUnion::new(union_type.clone(), Undefined::new(union_type), 1, "x")
+                    .into(),
+            ],
+            Variable::new("x"),
+        );
+        let original = module.clone();
+
+        transform(&mut module);
+
+        assert_eq!(module, original);
+    }
+
+    #[test]
+    fn keep_store_of_undefined_value() {
+        let module = create_module(
+            vec![Store::new(
+                types::Primitive::PointerInteger,
+                ArithmeticOperation::new(
+                    types::Primitive::PointerInteger,
+                    ArithmeticOperator::Add,
+                    Undefined::new(types::Primitive::PointerInteger),
+                    Primitive::PointerInteger(1),
+                ),
+                Variable::new("p"),
+            )
+            .into()],
+            Primitive::PointerInteger(0),
+        );
+
+        assert_eq!(
+            transform_module(module),
+            create_module(
+                vec![Store::new(
+                    types::Primitive::PointerInteger,
+                    Undefined::new(types::Primitive::PointerInteger),
+                    Variable::new("p"),
+                )
+                .into()],
+                Primitive::PointerInteger(0),
+            )
+        );
+    }
+
+    #[test]
+    fn keep_defined_operations() {
+        let module = create_module(
+            vec![If::new(
+                void_type(),
+                Primitive::Boolean(true),
+                Block::new(vec![], Branch::new(void_type(), void_value())),
+                Block::new(vec![], Branch::new(void_type(), void_value())),
+                "x",
+            )
+            .into()],
+            ArithmeticOperation::new(
+                types::Primitive::PointerInteger,
+                ArithmeticOperator::Add,
+                Primitive::PointerInteger(1),
+                Primitive::PointerInteger(2),
+            ),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
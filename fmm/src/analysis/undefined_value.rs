@@ -0,0 +1,315 @@
+mod error;
+
+use crate::{ir::*, types::Type};
+pub use error::*;
+
+// Checks if undefined values are stored, passed to calls, or returned or
+// branched in code which can reach returns from functions. Undefined values of
+// zero-sized types are ignored as they have no bits to leak and are introduced
+// by the C calling convention transform for zero-sized arguments.
+pub fn check(module: &Module) -> Result<(), UndefinedValueError> {
+    for definition in module.function_definitions() {
+        check_block(definition.body(), false)?;
+    }
+
+    Ok(())
+}
+
+// Returns if control can reach a return from the beginning of a block given
+// whether the one after the block can do so.
+fn check_block(block: &Block, continued: bool) -> Result<bool, UndefinedValueError> {
+    let mut live = match block.terminal_instruction() {
+        TerminalInstruction::Branch(branch) => {
+            if continued {
+                check_expression(branch.expression())?;
+            }
+
+            continued
+        }
+        TerminalInstruction::Return(return_) => {
+            check_expression(return_.expression())?;
+
+            true
+        }
+        TerminalInstruction::Unreachable => false,
+    };
+
+    for instruction in block.instructions().iter().rev() {
+        match instruction {
+            Instruction::AtomicStore(store) if live => check_expression(store.value())?,
+            Instruction::Call(call) if live => {
+                for argument in call.arguments() {
+                    check_expression(argument)?;
+                }
+            }
+            Instruction::If(if_) => {
+                let then = check_block(if_.then(), live)?;
+                let else_ = check_block(if_.else_(), live)?;
+
+                live = then || else_;
+            }
+            Instruction::Store(store) if live => check_expression(store.value())?,
+            _ => {}
+        }
+    }
+
+    Ok(live)
+}
+
+fn check_expression(expression: &Expression) -> Result<(), UndefinedValueError> {
+    match expression {
+        Expression::ArithmeticOperation(operation) => {
+            check_expression(operation.lhs())?;
+            check_expression(operation.rhs())
+        }
+        Expression::BitCast(bit_cast) => check_expression(bit_cast.expression()),
+        Expression::BitwiseNotOperation(operation) => check_expression(operation.value()),
+        Expression::BitwiseOperation(operation) => {
+            check_expression(operation.lhs())?;
+            check_expression(operation.rhs())
+        }
+        Expression::ComparisonOperation(operation) => {
+            check_expression(operation.lhs())?;
+            check_expression(operation.rhs())
+        }
+        Expression::PointerAddress(address) => {
+            check_expression(address.pointer())?;
+            check_expression(address.offset())
+        }
+        Expression::Record(record) => {
+            for field in record.fields() {
+                check_expression(field)?;
+            }
+
+            Ok(())
+        }
+        Expression::RecordAddress(address) => check_expression(address.pointer()),
+        Expression::Undefined(undefined) => {
+            if is_zero_sized(undefined.type_()) {
+                Ok(())
+            } else {
+                Err(UndefinedValueError::ReachableUndefinedValue(
+                    undefined.clone(),
+                ))
+            }
+        }
+        Expression::Union(union) => check_expression(union.member()),
+        Expression::UnionAddress(address) => check_expression(address.pointer()),
+        Expression::AlignOf(_)
+        | Expression::Primitive(_)
+        | Expression::SizeOf(_)
+        | Expression::Variable(_) => Ok(()),
+    }
+}
+
+fn is_zero_sized(type_: &Type) -> bool {
+    match type_ {
+        Type::Record(record) => record.fields().iter().all(is_zero_sized),
+        Type::Union(union) => union.members().iter().all(is_zero_sized),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::c_calling_convention,
+        types::{self, void_type},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn create_module(
+        instructions: Vec<Instruction>,
+        terminal: impl Into<TerminalInstruction>,
+    ) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new(
+                    "p",
+                    types::Pointer::new(types::Primitive::PointerInteger),
+                )],
+                void_type(),
+                Block::new(instructions, terminal),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn create_store() -> Instruction {
+        Store::new(
+            types::Primitive::PointerInteger,
+            ArithmeticOperation::new(
+                types::Primitive::PointerInteger,
+                ArithmeticOperator::Add,
+                Undefined::new(types::Primitive::PointerInteger),
+                Primitive::PointerInteger(1),
+            ),
+            Variable::new("p"),
+        )
+        .into()
+    }
+
+    #[test]
+    fn fail_to_check_stored_undefined_value() {
+        assert_eq!(
+            check(&create_module(
+                vec![create_store()],
+                Return::new(void_type(), void_value())
+            )),
+            Err(UndefinedValueError::ReachableUndefinedValue(
+                Undefined::new(types::Primitive::PointerInteger)
+            ))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_returned_undefined_value() {
+        assert_eq!(
+            check(&Module::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![FunctionDefinition::new(
+                    "f",
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    Block::new(
+                        vec![],
+                        Return::new(
+                            types::Primitive::PointerInteger,
+                            Undefined::new(types::Primitive::PointerInteger)
+                        )
+                    ),
+                    Default::default(),
+                )],
+            )),
+            Err(UndefinedValueError::ReachableUndefinedValue(
+                Undefined::new(types::Primitive::PointerInteger)
+            ))
+        );
+    }
+
+    #[test]
+    fn fail_to_check_undefined_value_before_if_with_return() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    create_store(),
+                    If::new(
+                        void_type(),
+                        Primitive::Boolean(true),
+                        Block::new(vec![], Return::new(void_type(), void_value())),
+                        Block::new(vec![], Branch::new(void_type(), void_value())),
+                        "x",
+                    )
+                    .into()
+                ],
+                TerminalInstruction::Unreachable
+            )),
+            Err(UndefinedValueError::ReachableUndefinedValue(
+                Undefined::new(types::Primitive::PointerInteger)
+            ))
+        );
+    }
+
+    #[test]
+    fn check_undefined_value_after_if_with_return() {
+        assert_eq!(
+            check(&create_module(
+                vec![
+                    If::new(
+                        void_type(),
+                        Primitive::Boolean(true),
+                        Block::new(vec![], Return::new(void_type(), void_value())),
+                        Block::new(vec![], Branch::new(void_type(), void_value())),
+                        "x",
+                    )
+                    .into(),
+                    create_store(),
+                ],
+                TerminalInstruction::Unreachable
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_undefined_value_in_branch_to_unreachable_instruction() {
+        assert_eq!(
+            check(&create_module(
+                vec![If::new(
+                    void_type(),
+                    Primitive::Boolean(true),
+                    Block::new(vec![], Return::new(void_type(), void_value())),
+                    Block::new(vec![create_store()], Branch::new(void_type(), void_value())),
+                    "x",
+                )
+                .into()],
+                TerminalInstruction::Unreachable
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_zero_sized_undefined_value() {
+        assert_eq!(
+            check(&create_module(
+                vec![],
+                Return::new(void_type(), Undefined::new(void_type()))
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_zero_sized_argument_in_c_calling_convention() {
+        let record_type = types::Record::new(vec![]);
+        let mut module = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![
+                    Argument::new("x", record_type.clone()),
+                    Argument::new("p", types::Pointer::new(record_type.clone())),
+                ],
+                void_type(),
+                Block::new(
+                    vec![Store::new(record_type, Variable::new("x"), Variable::new("p")).into()],
+                    Return::new(void_type(), void_value()),
+                ),
+                FunctionDefinitionOptions::new()
+                    .set_calling_convention(types::CallingConvention::Target),
+            )],
+        );
+
+        c_calling_convention::transform(&mut module, 8).unwrap();
+
+        assert_eq!(check(&module), Ok(()));
+    }
+
+    #[test]
+    fn check_undefined_value_in_unreachable_block() {
+        assert_eq!(
+            check(&create_module(
+                vec![If::new(
+                    void_type(),
+                    Primitive::Boolean(true),
+                    Block::new(vec![create_store()], TerminalInstruction::Unreachable),
+                    Block::new(vec![], Branch::new(void_type(), void_value())),
+                    "x",
+                )
+                .into()],
+                Return::new(void_type(), void_value())
+            )),
+            Ok(())
+        );
+    }
+}
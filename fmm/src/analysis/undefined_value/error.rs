@@ -0,0 +1,18 @@
+use crate::ir::*;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UndefinedValueError {
+    ReachableUndefinedValue(Undefined),
+}
+
+impl Display for UndefinedValueError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}", self)
+    }
+}
+
+impl Error for UndefinedValueError {}
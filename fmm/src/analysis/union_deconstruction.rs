@@ -0,0 +1,168 @@
+use super::variable_use;
+use crate::{ir::*, types};
+use fnv::FnvHashMap;
+
+// Replace loads of unions used only by deconstruction with loads of their
+// members. Loads are kept at their original positions so that they observe the
+// same memory states.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_function_definition(definition);
+    }
+}
+
+fn transform_function_definition(definition: &mut FunctionDefinition) {
+    let counts = variable_use::count(definition);
+    let mut loaded_names = FnvHashMap::default();
+    let mut deconstructions = FnvHashMap::default();
+
+    collect_names(definition.body(), &mut loaded_names, &mut deconstructions);
+
+    let deconstructions = deconstructions
+        .into_iter()
+        .filter(|(name, _)| loaded_names.contains_key(name) && counts.get(name) == Some(&1))
+        .map(|(name, (member_index, member_name))| {
+            (name.to_owned(), (member_index, member_name.to_owned()))
+        })
+        .collect::<FnvHashMap<_, _>>();
+
+    if deconstructions.is_empty() {
+        return;
+    }
+
+    transform_block(definition.body_mut(), &deconstructions);
+}
+
+fn collect_names<'a>(
+    block: &'a Block,
+    loaded_names: &mut FnvHashMap<&'a str, &'a types::Union>,
+    deconstructions: &mut FnvHashMap<&'a str, (usize, &'a str)>,
+) {
+    for instruction in block.instructions() {
+        match instruction {
+            Instruction::DeconstructUnion(deconstruct) => {
+                if let Expression::Variable(variable) = deconstruct.union() {
+                    deconstructions.insert(
+                        variable.name(),
+                        (deconstruct.member_index(), deconstruct.name()),
+                    );
+                }
+            }
+            Instruction::If(if_) => {
+                collect_names(if_.then(), loaded_names, deconstructions);
+                collect_names(if_.else_(), loaded_names, deconstructions);
+            }
+            Instruction::Load(load) => {
+                if let Some(type_) = load.type_().to_union() {
+                    loaded_names.insert(load.name(), type_);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn transform_block(block: &mut Block, deconstructions: &FnvHashMap<String, (usize, String)>) {
+    block
+        .instructions_mut()
+        .retain(|instruction| match instruction {
+            Instruction::DeconstructUnion(deconstruct) => !matches!(
+                deconstruct.union(),
+                Expression::Variable(variable) if deconstructions.contains_key(variable.name())
+            ),
+            _ => true,
+        });
+
+    for instruction in block.instructions_mut() {
+        match instruction {
+            Instruction::If(if_) => {
+                transform_block(if_.then_mut(), deconstructions);
+                transform_block(if_.else_mut(), deconstructions);
+            }
+            Instruction::Load(load) => {
+                if let (Some((member_index, member_name)), Some(type_)) =
+                    (deconstructions.get(load.name()), load.type_().to_union())
+                {
+                    *instruction = Load::new(
+                        type_.members()[*member_index].clone(),
+                        UnionAddress::new(type_.clone(), load.pointer().clone(), *member_index),
+                        member_name,
+                    )
+                    .set_alignment(load.alignment())
+                    .set_dereferenceable_bytes(load.dereferenceable_bytes())
+                    .into();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::type_check;
+    use pretty_assertions::assert_eq;
+
+    fn union_type() -> types::Union {
+        types::Union::new(vec![
+            types::Primitive::Float64.into(),
+            types::Primitive::PointerInteger.into(),
+        ])
+    }
+
+    fn create_module(instructions: Vec<Instruction>) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new("p", types::Pointer::new(union_type()))],
+                types::Primitive::PointerInteger,
+                Block::new(
+                    instructions,
+                    Return::new(types::Primitive::PointerInteger, Variable::new("y")),
+                ),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn lower_deconstruction_of_loaded_union() {
+        assert_eq!(
+            transform_module(create_module(vec![
+                Load::new(union_type(), Variable::new("p"), "x").into(),
+                DeconstructUnion::new(union_type(), Variable::new("x"), 1, "y").into(),
+            ])),
+            create_module(vec![Load::new(
+                types::Primitive::PointerInteger,
+                UnionAddress::new(union_type(), Variable::new("p"), 1),
+                "y"
+            )
+            .into()])
+        );
+    }
+
+    #[test]
+    fn keep_union_used_elsewhere() {
+        let module = create_module(vec![
+            Load::new(union_type(), Variable::new("p"), "x").into(),
+            DeconstructUnion::new(union_type(), Variable::new("x"), 1, "y").into(),
+            Store::new(union_type(), Variable::new("x"), Variable::new("p")).into(),
+        ]);
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::ir::*;
+
+// Remove instructions after if instructions neither of whose branches falls
+// through, such as ones with inlined calls of functions which never return.
+pub fn transform(module: &mut Module) {
+    for definition in module.function_definitions_mut() {
+        transform_block(definition.body_mut());
+    }
+}
+
+fn transform_block(block: &mut Block) {
+    let mut length = None;
+
+    for (index, instruction) in block.instructions_mut().iter_mut().enumerate() {
+        if let Instruction::If(if_) = instruction {
+            transform_block(if_.then_mut());
+            transform_block(if_.else_mut());
+
+            if !if_.then().terminal_instruction().is_branch()
+                && !if_.else_().terminal_instruction().is_branch()
+            {
+                length = Some(index + 1);
+                break;
+            }
+        }
+    }
+
+    if let Some(length) = length {
+        block.instructions_mut().truncate(length);
+        *block.terminal_instruction_mut() = TerminalInstruction::Unreachable;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::type_check,
+        types::{self, void_type},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn create_module(
+        instructions: Vec<Instruction>,
+        terminal_instruction: impl Into<TerminalInstruction>,
+    ) -> Module {
+        Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![Argument::new("x", types::Primitive::Boolean)],
+                void_type(),
+                Block::new(instructions, terminal_instruction),
+                Default::default(),
+            )],
+        )
+    }
+
+    fn create_if(then: Block, else_: Block) -> Instruction {
+        If::new(void_type(), Variable::new("x"), then, else_, "y").into()
+    }
+
+    fn create_free() -> Instruction {
+        FreeHeap::new(Undefined::new(types::generic_pointer_type())).into()
+    }
+
+    fn transform_module(mut module: Module) -> Module {
+        type_check::check(&module).unwrap();
+
+        transform(&mut module);
+
+        type_check::check(&module).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn remove_instructions_after_unreachable_branches() {
+        let if_ = create_if(
+            Block::new(vec![], TerminalInstruction::Unreachable),
+            Block::new(vec![], Return::new(void_type(), void_value())),
+        );
+
+        assert_eq!(
+            transform_module(create_module(
+                vec![if_.clone(), create_free()],
+                Return::new(void_type(), void_value()),
+            )),
+            create_module(vec![if_], TerminalInstruction::Unreachable)
+        );
+    }
+
+    #[test]
+    fn remove_instructions_after_nested_unreachable_branches() {
+        let unreachable_block = Block::new(vec![], TerminalInstruction::Unreachable);
+
+        assert_eq!(
+            transform_module(create_module(
+                vec![
+                    create_if(
+                        Block::new(
+                            vec![
+                                create_if(unreachable_block.clone(), unreachable_block.clone()),
+                                create_free(),
+                            ],
+                            Branch::new(void_type(), void_value()),
+                        ),
+                        unreachable_block.clone(),
+                    ),
+                    create_free(),
+                ],
+                Return::new(void_type(), void_value()),
+            )),
+            create_module(
+                vec![create_if(
+                    Block::new(
+                        vec![create_if(
+                            unreachable_block.clone(),
+                            unreachable_block.clone()
+                        )],
+                        TerminalInstruction::Unreachable,
+                    ),
+                    unreachable_block,
+                )],
+                TerminalInstruction::Unreachable,
+            )
+        );
+    }
+
+    #[test]
+    fn keep_instructions_after_branch() {
+        let module = create_module(
+            vec![
+                create_if(
+                    Block::new(vec![], TerminalInstruction::Unreachable),
+                    Block::new(vec![], Branch::new(void_type(), void_value())),
+                ),
+                create_free(),
+            ],
+            Return::new(void_type(), void_value()),
+        );
+
+        assert_eq!(transform_module(module.clone()), module);
+    }
+}
@@ -71,7 +71,11 @@ fn check_block<'a>(
     for instruction in block.instructions() {
         match instruction {
             Instruction::AllocateHeap(allocate) => check_expression(allocate.size(), variables)?,
-            Instruction::AllocateStack(_) => {}
+            Instruction::AllocateStack(allocate) => {
+                if let Some(count) = allocate.count() {
+                    check_expression(count, variables)?;
+                }
+            }
             Instruction::AtomicLoad(load) => check_expression(load.pointer(), variables)?,
             Instruction::AtomicOperation(operation) => {
                 check_expression(operation.pointer(), variables)?;
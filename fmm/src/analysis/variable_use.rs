@@ -0,0 +1,39 @@
+use crate::ir::*;
+use fnv::FnvHashMap;
+
+pub fn count(definition: &FunctionDefinition) -> FnvHashMap<&str, usize> {
+    let mut counts = FnvHashMap::default();
+
+    count_in_block(definition.body(), &mut counts);
+
+    counts
+}
+
+fn count_in_block<'a>(block: &'a Block, counts: &mut FnvHashMap<&'a str, usize>) {
+    for instruction in block.instructions() {
+        count_in_instruction(instruction, counts);
+    }
+
+    match block.terminal_instruction() {
+        TerminalInstruction::Branch(branch) => count_in_expression(branch.expression(), counts),
+        TerminalInstruction::Return(return_) => count_in_expression(return_.expression(), counts),
+        TerminalInstruction::Unreachable => {}
+    }
+}
+
+fn count_in_instruction<'a>(instruction: &'a Instruction, counts: &mut FnvHashMap<&'a str, usize>) {
+    for expression in instruction.operands() {
+        count_in_expression(expression, counts);
+    }
+
+    if let Instruction::If(if_) = instruction {
+        count_in_block(if_.then(), counts);
+        count_in_block(if_.else_(), counts);
+    }
+}
+
+fn count_in_expression<'a>(expression: &'a Expression, counts: &mut FnvHashMap<&'a str, usize>) {
+    for name in expression.referenced_variables() {
+        *counts.entry(name).or_default() += 1;
+    }
+}
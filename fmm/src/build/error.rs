@@ -7,6 +7,8 @@ use std::{
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BuildError {
     FunctionExpected(Type),
+    IntegerExpected(Type),
+    MemberIndexOutOfBounds(Type, usize),
     PointerExpected(Type),
     PrimitiveExpected(Type),
     RecordExpected(Type),
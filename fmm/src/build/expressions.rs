@@ -105,6 +105,25 @@ pub fn pointer_address(
     ))
 }
 
+// Offsets of pointer addresses are in elements while ones here are in bytes.
+pub fn byte_address(
+    pointer: impl Into<TypedExpression>,
+    offset: impl Into<TypedExpression>,
+) -> Result<BitCast, BuildError> {
+    let pointer = pointer.into();
+    let byte_pointer_type = types::Pointer::new(types::Primitive::Integer8);
+
+    pointer
+        .type_()
+        .to_pointer()
+        .ok_or_else(|| BuildError::PointerExpected(pointer.type_().clone()))?;
+
+    Ok(bit_cast(
+        pointer.type_().clone(),
+        pointer_address(bit_cast(byte_pointer_type, pointer.clone()), offset)?,
+    ))
+}
+
 pub fn record(fields: Vec<TypedExpression>) -> Record {
     Record::new(
         types::Record::new(fields.iter().map(|field| field.type_().clone()).collect()),
@@ -163,6 +182,141 @@ pub fn union_address(
     ))
 }
 
+// Unions store all their members at offset 0.
+pub fn union_member_pointer(
+    pointer: impl Into<TypedExpression>,
+    member_index: usize,
+) -> Result<BitCast, BuildError> {
+    let pointer = pointer.into();
+    let element_type = pointer
+        .type_()
+        .to_pointer()
+        .ok_or_else(|| BuildError::PointerExpected(pointer.type_().clone()))?
+        .element();
+    let member_type = element_type
+        .to_union()
+        .ok_or_else(|| BuildError::UnionExpected(element_type.clone()))?
+        .members()
+        .get(member_index)
+        .ok_or_else(|| BuildError::MemberIndexOutOfBounds(element_type.clone(), member_index))?
+        .clone();
+
+    Ok(bit_cast(types::Pointer::new(member_type), pointer))
+}
+
 pub fn variable(name: impl Into<String>, type_: impl Into<Type>) -> TypedExpression {
     TypedExpression::new(Variable::new(name), type_)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod pointer_address {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn calculate_element_address() {
+            let pointer_type = types::Pointer::new(types::Primitive::Integer64);
+
+            assert_eq!(
+                TypedExpression::from(
+                    pointer_address(
+                        variable("x", pointer_type.clone()),
+                        Primitive::PointerInteger(3)
+                    )
+                    .unwrap()
+                ),
+                TypedExpression::new(
+                    PointerAddress::new(
+                        pointer_type.clone(),
+                        Variable::new("x"),
+                        Primitive::PointerInteger(3)
+                    ),
+                    pointer_type
+                )
+            );
+        }
+
+        #[test]
+        fn calculate_byte_address() {
+            let pointer_type = types::Pointer::new(types::Primitive::Integer64);
+            let byte_pointer_type = types::Pointer::new(types::Primitive::Integer8);
+
+            assert_eq!(
+                TypedExpression::from(
+                    byte_address(
+                        variable("x", pointer_type.clone()),
+                        Primitive::PointerInteger(3)
+                    )
+                    .unwrap()
+                ),
+                TypedExpression::new(
+                    BitCast::new(
+                        byte_pointer_type.clone(),
+                        pointer_type.clone(),
+                        PointerAddress::new(
+                            byte_pointer_type.clone(),
+                            BitCast::new(
+                                pointer_type.clone(),
+                                byte_pointer_type,
+                                Variable::new("x")
+                            ),
+                            Primitive::PointerInteger(3)
+                        )
+                    ),
+                    pointer_type
+                )
+            );
+        }
+    }
+
+    mod union_member_pointer {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn create_union() -> types::Union {
+            types::Union::new(vec![
+                types::Primitive::Integer8.into(),
+                types::Primitive::Float64.into(),
+            ])
+        }
+
+        #[test]
+        fn calculate_member_pointer() {
+            let pointer = variable("x", types::Pointer::new(create_union()));
+
+            assert_eq!(
+                TypedExpression::from(union_member_pointer(pointer.clone(), 1).unwrap()),
+                TypedExpression::new(
+                    BitCast::new(
+                        pointer.type_().clone(),
+                        types::Pointer::new(types::Primitive::Float64),
+                        Variable::new("x"),
+                    ),
+                    types::Pointer::new(types::Primitive::Float64)
+                )
+            );
+        }
+
+        #[test]
+        fn fail_to_calculate_member_pointer_out_of_bounds() {
+            assert_eq!(
+                union_member_pointer(variable("x", types::Pointer::new(create_union())), 2),
+                Err(BuildError::MemberIndexOutOfBounds(create_union().into(), 2))
+            );
+        }
+
+        #[test]
+        fn fail_to_calculate_member_pointer_of_non_union() {
+            assert_eq!(
+                union_member_pointer(
+                    variable("x", types::Pointer::new(types::Primitive::Integer8)),
+                    0
+                ),
+                Err(BuildError::UnionExpected(types::Primitive::Integer8.into()))
+            );
+        }
+    }
+}
@@ -1,16 +1,40 @@
 use super::{
-    error::BuildError, expressions::variable, name_generator::NameGenerator, typed_expression::*,
+    error::BuildError,
+    expressions::{comparison_operation, variable},
+    name_generator::NameGenerator,
+    typed_expression::*,
 };
 use crate::{
+    analysis::type_check,
     ir::*,
     types::{self, generic_pointer_type, void_type, Type},
 };
+use fnv::FnvHashMap;
 use std::{cell::RefCell, mem::take, rc::Rc};
 
 #[derive(Debug)]
 pub struct InstructionBuilder {
     name_generator: Rc<RefCell<NameGenerator>>,
     instructions: RefCell<Vec<Instruction>>,
+    scope: Option<Rc<Scope>>,
+}
+
+// Variables defined in blocks are visible only in the blocks and their child
+// blocks.
+#[derive(Debug, Default)]
+struct Scope {
+    variables: RefCell<FnvHashMap<String, Type>>,
+    parent: Option<Rc<Scope>>,
+}
+
+impl Scope {
+    fn get(&self, name: &str) -> Option<Type> {
+        self.variables
+            .borrow()
+            .get(name)
+            .cloned()
+            .or_else(|| self.parent.as_ref()?.get(name))
+    }
 }
 
 impl InstructionBuilder {
@@ -18,6 +42,7 @@ impl InstructionBuilder {
         Self {
             name_generator,
             instructions: vec![].into(),
+            scope: None,
         }
     }
 
@@ -25,11 +50,38 @@ impl InstructionBuilder {
         Self {
             name_generator,
             instructions: Vec::with_capacity(capacity).into(),
+            scope: None,
+        }
+    }
+
+    // Checked builders type-check every instruction added to them against
+    // given variables and ones defined by the instructions, and panic on
+    // errors.
+    pub fn checked(
+        name_generator: Rc<RefCell<NameGenerator>>,
+        variables: FnvHashMap<String, Type>,
+    ) -> Self {
+        Self {
+            name_generator,
+            instructions: vec![].into(),
+            scope: Some(Rc::new(Scope {
+                variables: variables.into(),
+                parent: None,
+            })),
         }
     }
 
     fn clone_empty(&self) -> Self {
-        Self::new(self.name_generator.clone())
+        Self {
+            name_generator: self.name_generator.clone(),
+            instructions: vec![].into(),
+            scope: self.scope.as_ref().map(|scope| {
+                Rc::new(Scope {
+                    variables: Default::default(),
+                    parent: Some(scope.clone()),
+                })
+            }),
+        }
     }
 
     pub fn allocate_heap(&self, size: impl Into<TypedExpression>) -> TypedExpression {
@@ -65,6 +117,23 @@ impl InstructionBuilder {
         TypedExpression::new(Variable::new(name), types::Pointer::new(type_))
     }
 
+    pub fn allocate_stack_array(
+        &self,
+        type_: impl Into<Type>,
+        count: impl Into<TypedExpression>,
+    ) -> TypedExpression {
+        let name = self.generate_name();
+        let type_ = type_.into();
+
+        self.add_instruction(AllocateStack::array(
+            type_.clone(),
+            count.into().expression().clone(),
+            &name,
+        ));
+
+        TypedExpression::new(Variable::new(name), types::Pointer::new(type_))
+    }
+
     pub fn atomic_load(
         &self,
         pointer: impl Into<TypedExpression>,
@@ -116,6 +185,31 @@ impl InstructionBuilder {
         Ok(variable(name, type_))
     }
 
+    pub fn atomic_operation_without_result(
+        &self,
+        operator: AtomicOperator,
+        pointer: impl Into<TypedExpression>,
+        value: impl Into<TypedExpression>,
+        ordering: AtomicOrdering,
+    ) -> Result<(), BuildError> {
+        let pointer = pointer.into();
+        let value = value.into();
+        let type_ = value
+            .type_()
+            .to_primitive()
+            .ok_or_else(|| BuildError::PrimitiveExpected(value.type_().clone()))?;
+
+        self.add_instruction(AtomicOperation::without_result(
+            type_,
+            operator,
+            pointer.expression().clone(),
+            value.expression().clone(),
+            ordering,
+        ));
+
+        Ok(())
+    }
+
     pub fn atomic_store(
         &self,
         value: impl Into<TypedExpression>,
@@ -136,7 +230,7 @@ impl InstructionBuilder {
     pub fn call(
         &self,
         function: impl Into<TypedExpression>,
-        arguments: Vec<TypedExpression>,
+        arguments: impl IntoIterator<Item = impl Into<TypedExpression>>,
     ) -> Result<TypedExpression, BuildError> {
         let function = function.into();
         let type_ = function
@@ -150,9 +244,8 @@ impl InstructionBuilder {
             type_.clone(),
             function.expression().clone(),
             arguments
-                .iter()
-                .map(|typed_expression| typed_expression.expression())
-                .cloned()
+                .into_iter()
+                .map(|typed_expression| typed_expression.into().expression().clone())
                 .collect(),
             &name,
         ));
@@ -270,6 +363,28 @@ impl InstructionBuilder {
         Ok(variable(name, type_))
     }
 
+    pub fn if_nonzero<E: From<BuildError>>(
+        &self,
+        value: impl Into<TypedExpression>,
+        then: impl Fn(Self) -> Result<Block, E>,
+        else_: impl Fn(Self) -> Result<Block, E>,
+    ) -> Result<TypedExpression, E> {
+        let value = value.into();
+        let zero = match value.type_() {
+            Type::Primitive(types::Primitive::Integer8) => Primitive::Integer8(0),
+            Type::Primitive(types::Primitive::Integer32) => Primitive::Integer32(0),
+            Type::Primitive(types::Primitive::Integer64) => Primitive::Integer64(0),
+            Type::Primitive(types::Primitive::PointerInteger) => Primitive::PointerInteger(0),
+            type_ => return Err(BuildError::IntegerExpected(type_.clone()).into()),
+        };
+
+        self.if_(
+            comparison_operation(ComparisonOperator::NotEqual, value, zero)?,
+            then,
+            else_,
+        )
+    }
+
     pub fn load(&self, pointer: impl Into<TypedExpression>) -> Result<TypedExpression, BuildError> {
         let pointer = pointer.into();
         let type_ = pointer
@@ -347,11 +462,344 @@ impl InstructionBuilder {
         self.instructions.into_inner()
     }
 
+    pub fn into_block(self, terminal_instruction: impl Into<TerminalInstruction>) -> Block {
+        Block::new(self.into_instructions(), terminal_instruction)
+    }
+
     pub fn add_instruction(&self, instruction: impl Into<Instruction>) {
-        self.instructions.borrow_mut().push(instruction.into());
+        let instruction = instruction.into();
+
+        if let Some(scope) = &self.scope {
+            if let Err(error) = type_check::check_instruction(
+                &instruction,
+                &instruction
+                    .operands()
+                    .into_iter()
+                    .flat_map(Expression::referenced_variables)
+                    .filter_map(|name| Some((name, scope.get(name)?)))
+                    .collect(),
+            ) {
+                panic!("invalid instruction {:?}: {}", instruction, error);
+            }
+
+            if let Some((name, type_)) = instruction.value() {
+                scope.variables.borrow_mut().insert(name.into(), type_);
+            }
+        }
+
+        self.instructions.borrow_mut().push(instruction);
     }
 
     fn generate_name(&self) -> String {
         self.name_generator.borrow_mut().generate()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_builder() -> InstructionBuilder {
+        InstructionBuilder::new(Rc::new(NameGenerator::new("x").into()))
+    }
+
+    mod checked {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn create_checked_builder() -> InstructionBuilder {
+            InstructionBuilder::checked(
+                Rc::new(NameGenerator::new("x").into()),
+                [(
+                    "p".into(),
+                    types::Pointer::new(types::Primitive::Integer64).into(),
+                )]
+                .into_iter()
+                .collect(),
+            )
+        }
+
+        #[test]
+        fn add_valid_instructions() {
+            let builder = create_checked_builder();
+            let pointer = variable("p", types::Pointer::new(types::Primitive::Integer64));
+
+            let value = builder.load(pointer.clone()).unwrap();
+            builder.store(value, pointer);
+
+            assert_eq!(builder.into_instructions().len(), 2);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panic_on_store_of_mismatched_type() {
+            create_checked_builder().add_instruction(Store::new(
+                types::Primitive::Float64,
+                Primitive::Float64(42.0),
+                Variable::new("p"),
+            ));
+        }
+
+        #[test]
+        fn use_variables_of_parent_blocks_in_branches() {
+            let builder = create_checked_builder();
+            let pointer = variable("p", types::Pointer::new(types::Primitive::Integer64));
+            let value = builder.load(pointer.clone()).unwrap();
+
+            let result = builder
+                .if_::<BuildError>(
+                    Primitive::Boolean(true),
+                    |builder| Ok(builder.branch(value.clone())),
+                    |builder| Ok(builder.branch(builder.load(pointer.clone())?)),
+                )
+                .unwrap();
+            builder.store(result, pointer);
+
+            assert_eq!(builder.into_instructions().len(), 3);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panic_on_variable_of_other_branch() {
+            let builder = create_checked_builder();
+            let pointer = variable("p", types::Pointer::new(types::Primitive::Integer64));
+
+            builder
+                .if_::<BuildError>(
+                    Primitive::Boolean(true),
+                    |builder| {
+                        builder.load(pointer.clone())?;
+
+                        Ok(builder.branch(void_value()))
+                    },
+                    |builder| {
+                        builder.store(variable("x0", types::Primitive::Integer64), pointer.clone());
+
+                        Ok(builder.branch(void_value()))
+                    },
+                )
+                .unwrap();
+        }
+
+        #[test]
+        #[should_panic]
+        fn panic_on_variable_of_branch_after_if() {
+            let builder = create_checked_builder();
+            let pointer = variable("p", types::Pointer::new(types::Primitive::Integer64));
+
+            builder
+                .if_::<BuildError>(
+                    Primitive::Boolean(true),
+                    |builder| {
+                        builder.load(pointer.clone())?;
+
+                        Ok(builder.branch(void_value()))
+                    },
+                    |builder| Ok(builder.branch(void_value())),
+                )
+                .unwrap();
+            builder.store(variable("x0", types::Primitive::Integer64), pointer);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panic_on_unknown_variable() {
+            create_checked_builder().add_instruction(Load::new(
+                types::Primitive::Integer64,
+                Variable::new("q"),
+                "x",
+            ));
+        }
+    }
+
+    mod uniform_operands {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn build_instructions_with_expression_and_type_pairs() {
+            let builder = create_builder();
+            let pointer_type = types::Pointer::new(types::Primitive::Integer64);
+            let function_type = types::Function::new(
+                vec![types::Primitive::Integer64.into()],
+                types::Primitive::Integer64,
+                types::CallingConvention::Source,
+            );
+
+            let value = builder
+                .load((Variable::new("p"), pointer_type.clone()))
+                .unwrap();
+            let result = builder
+                .call(
+                    (Variable::new("f"), function_type.clone()),
+                    [(Variable::new("y"), types::Primitive::Integer64)],
+                )
+                .unwrap();
+            builder.store(result, (Variable::new("p"), pointer_type));
+
+            assert_eq!(value, variable("x0", types::Primitive::Integer64));
+            assert_eq!(
+                builder.into_instructions(),
+                vec![
+                    Load::new(types::Primitive::Integer64, Variable::new("p"), "x0").into(),
+                    Call::new(
+                        function_type,
+                        Variable::new("f"),
+                        vec![Variable::new("y").into()],
+                        "x1",
+                    )
+                    .into(),
+                    Store::new(
+                        types::Primitive::Integer64,
+                        Variable::new("x1"),
+                        Variable::new("p"),
+                    )
+                    .into(),
+                ]
+            );
+        }
+    }
+
+    mod allocate_stack_array {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn allocate_array_of_dynamic_length() {
+            let builder = InstructionBuilder::checked(
+                Rc::new(NameGenerator::new("x").into()),
+                [("n".into(), types::Primitive::PointerInteger.into())]
+                    .into_iter()
+                    .collect(),
+            );
+
+            let pointer = builder.allocate_stack_array(
+                types::Primitive::Integer64,
+                variable("n", types::Primitive::PointerInteger),
+            );
+
+            assert_eq!(
+                pointer.type_(),
+                &types::Pointer::new(types::Primitive::Integer64).into()
+            );
+            assert_eq!(
+                builder.into_instructions(),
+                vec![
+                    AllocateStack::array(types::Primitive::Integer64, Variable::new("n"), "x0")
+                        .into()
+                ]
+            );
+        }
+    }
+
+    mod load {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn load_through_pointer() {
+            let builder = create_builder();
+
+            assert_eq!(
+                builder.load(variable(
+                    "p",
+                    types::Pointer::new(types::Primitive::Integer64)
+                )),
+                Ok(variable("x0", types::Primitive::Integer64))
+            );
+            assert_eq!(
+                builder.into_instructions(),
+                vec![Load::new(types::Primitive::Integer64, Variable::new("p"), "x0").into()]
+            );
+        }
+
+        #[test]
+        fn fail_to_load_through_non_pointer() {
+            let builder = create_builder();
+
+            assert_eq!(
+                builder.load(variable("p", types::Primitive::Integer64)),
+                Err(BuildError::PointerExpected(
+                    types::Primitive::Integer64.into()
+                ))
+            );
+            assert_eq!(builder.into_instructions(), vec![]);
+        }
+    }
+
+    mod block {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn into_block() {
+            let builder = create_builder();
+            let pointer = variable("p", types::Pointer::new(types::Primitive::Integer64));
+
+            let value = builder.load(pointer).unwrap();
+
+            assert_eq!(
+                builder.into_block(Return::new(
+                    value.type_().clone(),
+                    value.expression().clone()
+                )),
+                Block::new(
+                    vec![Load::new(types::Primitive::Integer64, Variable::new("p"), "x0").into()],
+                    Return::new(types::Primitive::Integer64, Variable::new("x0"))
+                )
+            );
+        }
+    }
+
+    mod if_nonzero {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn build_if_nonzero() {
+            let builder = create_builder();
+            let create_block = || Block::new(vec![], Branch::new(void_type(), void_value()));
+
+            builder
+                .if_nonzero::<BuildError>(
+                    variable("x", types::Primitive::Integer64),
+                    |_| Ok(create_block()),
+                    |_| Ok(create_block()),
+                )
+                .unwrap();
+
+            assert_eq!(
+                builder.into_instructions(),
+                vec![If::new(
+                    void_type(),
+                    ComparisonOperation::new(
+                        types::Primitive::Integer64,
+                        ComparisonOperator::NotEqual,
+                        Variable::new("x"),
+                        Primitive::Integer64(0),
+                    ),
+                    create_block(),
+                    create_block(),
+                    "x0",
+                )
+                .into()]
+            );
+        }
+
+        #[test]
+        fn fail_to_build_if_nonzero_with_float() {
+            let builder = create_builder();
+
+            assert_eq!(
+                builder.if_nonzero::<BuildError>(
+                    variable("x", types::Primitive::Float64),
+                    |builder| Ok(builder.unreachable()),
+                    |builder| Ok(builder.unreachable()),
+                ),
+                Err(BuildError::IntegerExpected(
+                    types::Primitive::Float64.into()
+                ))
+            );
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use super::{
-    instruction_builder::InstructionBuilder, name_generator::NameGenerator, typed_expression::*,
+    error::BuildError, instruction_builder::InstructionBuilder, name_generator::NameGenerator,
+    typed_expression::*,
 };
 use crate::{
+    analysis::retain,
     ir::*,
     types::{self, Type},
 };
+use fnv::{FnvHashMap, FnvHashSet};
 use std::{cell::RefCell, rc::Rc};
 
 #[derive(Debug)]
@@ -14,6 +17,7 @@ pub struct ModuleBuilder {
     pub function_declarations: RefCell<Vec<FunctionDeclaration>>,
     pub variable_definitions: RefCell<Vec<VariableDefinition>>,
     pub function_definitions: RefCell<Vec<FunctionDefinition>>,
+    c_strings: RefCell<FnvHashMap<Vec<u8>, TypedExpression>>,
 }
 
 impl ModuleBuilder {
@@ -24,6 +28,7 @@ impl ModuleBuilder {
             function_declarations: Default::default(),
             variable_definitions: Default::default(),
             function_definitions: Default::default(),
+            c_strings: Default::default(),
         }
     }
 
@@ -36,6 +41,116 @@ impl ModuleBuilder {
         )
     }
 
+    // Variable definitions are put into the first module. Definitions in the
+    // other modules are referenced through declarations and so definitions
+    // of internal linkage referenced across modules are made external.
+    pub fn split_at(self, max_functions: usize) -> Vec<Module> {
+        let variable_declarations = self.variable_declarations.into_inner();
+        let function_declarations = self.function_declarations.into_inner();
+        let mut variable_definitions = self.variable_definitions.into_inner();
+        let mut function_definitions = self.function_definitions.into_inner();
+        let max_functions = max_functions.max(1);
+        let declared_names = variable_declarations
+            .iter()
+            .map(|declaration| declaration.name())
+            .chain(
+                function_declarations
+                    .iter()
+                    .map(|declaration| declaration.name()),
+            )
+            .collect::<FnvHashSet<_>>();
+
+        if function_definitions.len() > max_functions {
+            let names = collect_names_referenced_across_partitions(
+                &variable_definitions,
+                &function_definitions,
+                max_functions,
+            );
+
+            for definition in &mut variable_definitions {
+                if names.contains(definition.name())
+                    && definition.options().linkage() == Linkage::Internal
+                {
+                    *definition = VariableDefinition::new(
+                        definition.name(),
+                        definition.body().clone(),
+                        definition.type_().clone(),
+                        definition.options().clone().set_linkage(Linkage::External),
+                    );
+                }
+            }
+
+            for definition in &mut function_definitions {
+                if names.contains(definition.name())
+                    && definition.options().linkage() == Linkage::Internal
+                {
+                    *definition.options_mut() =
+                        definition.options().clone().set_linkage(Linkage::External);
+                }
+            }
+        }
+
+        let partitions = if function_definitions.is_empty() {
+            vec![&[][..]]
+        } else {
+            function_definitions
+                .chunks(max_functions)
+                .collect::<Vec<_>>()
+        };
+
+        partitions
+            .into_iter()
+            .enumerate()
+            .map(|(index, partition)| {
+                let names = partition
+                    .iter()
+                    .map(|definition| definition.name())
+                    .collect::<FnvHashSet<_>>();
+
+                Module::new(
+                    variable_declarations
+                        .iter()
+                        .cloned()
+                        .chain(
+                            variable_definitions
+                                .iter()
+                                .filter(|definition| {
+                                    index != 0 && !declared_names.contains(definition.name())
+                                })
+                                .map(|definition| {
+                                    VariableDeclaration::new(
+                                        definition.name(),
+                                        definition.type_().clone(),
+                                    )
+                                }),
+                        )
+                        .collect(),
+                    function_declarations
+                        .iter()
+                        .cloned()
+                        .chain(
+                            function_definitions
+                                .iter()
+                                .filter(|definition| {
+                                    !names.contains(definition.name())
+                                        && !declared_names.contains(definition.name())
+                                })
+                                .map(|definition| {
+                                    FunctionDeclaration::new(definition.name(), definition.type_())
+                                }),
+                        )
+                        .collect(),
+                    if index == 0 {
+                        variable_definitions.clone()
+                    } else {
+                        vec![]
+                    },
+                    partition.to_vec(),
+                )
+            })
+            .collect()
+    }
+
     pub fn declare_variable(
         &self,
         name: impl Into<String>,
@@ -126,6 +241,32 @@ impl ModuleBuilder {
         Ok(TypedExpression::new(Variable::new(name), type_))
     }
 
+    // Instructions built by cleanup functions are inserted before every return
+    // instruction in function bodies.
+    pub fn define_function_with_cleanup<E>(
+        &self,
+        name: impl Into<String>,
+        arguments: Vec<Argument>,
+        result_type: impl Into<Type>,
+        body: impl Fn(InstructionBuilder) -> Result<Block, E>,
+        cleanup: impl Fn(&InstructionBuilder) -> Result<(), E>,
+        options: FunctionDefinitionOptions,
+    ) -> Result<TypedExpression, E> {
+        self.define_function(
+            name,
+            arguments,
+            result_type,
+            |builder| {
+                let mut block = body(builder)?;
+
+                self.insert_cleanup(&mut block, &cleanup)?;
+
+                Ok(block)
+            },
+            options,
+        )
+    }
+
     pub fn define_anonymous_function<E>(
         &self,
         origin_name: String,
@@ -143,9 +284,125 @@ impl ModuleBuilder {
         )
     }
 
+    pub fn define_c_entry<E>(
+        &self,
+        name: impl Into<String>,
+        body: impl Fn(InstructionBuilder) -> Result<Block, E>,
+    ) -> Result<TypedExpression, E> {
+        self.define_function(
+            name,
+            vec![
+                Argument::new("argc", types::Primitive::Integer32),
+                Argument::new(
+                    "argv",
+                    types::Pointer::new(types::Pointer::new(types::Primitive::Integer8)),
+                ),
+            ],
+            types::Primitive::Integer32,
+            body,
+            FunctionDefinitionOptions::new()
+                .set_calling_convention(types::CallingConvention::Target),
+        )
+    }
+
+    // Aggregate arguments and results of wrappers are passed by pointers after
+    // the C calling convention pass.
+    pub fn export_as_c(
+        &self,
+        source_function: impl Into<TypedExpression>,
+        c_name: &str,
+    ) -> Result<TypedExpression, BuildError> {
+        let source_function = source_function.into();
+        let type_ = source_function
+            .type_()
+            .to_function()
+            .ok_or_else(|| BuildError::FunctionExpected(source_function.type_().clone()))?
+            .clone();
+        let arguments = type_
+            .arguments()
+            .iter()
+            .map(|type_| Argument::new(self.generate_name(), type_.clone()))
+            .collect::<Vec<_>>();
+
+        self.define_function(
+            c_name,
+            arguments.clone(),
+            type_.result().clone(),
+            |builder| {
+                Ok(builder.return_(builder.call(
+                    source_function.clone(),
+                    arguments.iter().map(|argument| {
+                        TypedExpression::new(
+                            Variable::new(argument.name()),
+                            argument.type_().clone(),
+                        )
+                    }),
+                )?))
+            },
+            FunctionDefinitionOptions::new()
+                .set_calling_convention(types::CallingConvention::Target),
+        )
+    }
+
+    // Strings are defined as records of bytes because there is no array type.
+    pub fn define_c_string(&self, bytes: &[u8]) -> TypedExpression {
+        if let Some(string) = self.c_strings.borrow().get(bytes) {
+            return string.clone();
+        }
+
+        let record = Record::new(
+            types::Record::new(vec![types::Primitive::Integer8.into(); bytes.len() + 1]),
+            bytes
+                .iter()
+                .chain([0].iter())
+                .map(|&byte| Primitive::Integer8(byte).into())
+                .collect(),
+        );
+        let pointer = self.define_anonymous_variable(
+            record.clone(),
+            VariableDefinitionOptions::new()
+                .set_address_named(false)
+                .set_mutable(false),
+        );
+        let string = TypedExpression::from(RecordAddress::new(
+            record.type_().clone(),
+            pointer.expression().clone(),
+            0,
+        ));
+
+        self.c_strings
+            .borrow_mut()
+            .insert(bytes.to_vec(), string.clone());
+
+        string
+    }
+
     pub fn generate_name(&self) -> String {
         self.name_generator.borrow_mut().generate()
     }
+
+    fn insert_cleanup<E>(
+        &self,
+        block: &mut Block,
+        cleanup: &impl Fn(&InstructionBuilder) -> Result<(), E>,
+    ) -> Result<(), E> {
+        for instruction in block.instructions_mut() {
+            if let Instruction::If(if_) = instruction {
+                self.insert_cleanup(if_.then_mut(), cleanup)?;
+                self.insert_cleanup(if_.else_mut(), cleanup)?;
+            }
+        }
+
+        if block.terminal_instruction().is_return() {
+            let builder = InstructionBuilder::new(self.name_generator.clone());
+
+            cleanup(&builder)?;
+
+            block.instructions_mut().extend(builder.into_instructions());
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for ModuleBuilder {
@@ -153,3 +410,470 @@ impl Default for ModuleBuilder {
         Self::new()
     }
 }
+
+fn collect_names_referenced_across_partitions(
+    variable_definitions: &[VariableDefinition],
+    function_definitions: &[FunctionDefinition],
+    max_functions: usize,
+) -> FnvHashSet<String> {
+    let mut names = FnvHashSet::default();
+
+    for (index, partition) in function_definitions.chunks(max_functions).enumerate() {
+        let mut defined_names = partition
+            .iter()
+            .map(|definition| definition.name())
+            .collect::<FnvHashSet<_>>();
+        let mut referenced_names = vec![];
+
+        for definition in partition {
+            retain::collect_referenced_names(definition.body(), &mut referenced_names);
+        }
+
+        if index == 0 {
+            for definition in variable_definitions {
+                defined_names.insert(definition.name());
+                referenced_names.extend(definition.referenced_symbols());
+            }
+        }
+
+        names.extend(
+            referenced_names
+                .into_iter()
+                .filter(|name| !defined_names.contains(name))
+                .map(String::from),
+        );
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn declare_function() {
+        let builder = ModuleBuilder::new();
+
+        let function = builder.declare_function(
+            "f",
+            types::Function::new(
+                vec![
+                    types::Primitive::Integer32.into(),
+                    types::Primitive::Float64.into(),
+                ],
+                types::Primitive::PointerInteger,
+                types::CallingConvention::Source,
+            ),
+        );
+
+        let type_ = function.as_function().unwrap();
+
+        assert_eq!(type_.arity(), 2);
+        assert_eq!(type_.result(), &types::Primitive::PointerInteger.into());
+    }
+
+    #[test]
+    fn define_c_entry() {
+        let builder = ModuleBuilder::new();
+
+        let function = builder
+            .define_c_entry("main", |builder| {
+                Ok::<_, ()>(builder.return_(build::variable("argc", types::Primitive::Integer32)))
+            })
+            .unwrap();
+
+        let type_ = types::Function::new(
+            vec![
+                types::Primitive::Integer32.into(),
+                types::Pointer::new(types::Pointer::new(types::Primitive::Integer8)).into(),
+            ],
+            types::Primitive::Integer32,
+            types::CallingConvention::Target,
+        );
+
+        assert_eq!(function, build::variable("main", type_.clone()));
+
+        let module = builder.into_module();
+
+        assert_eq!(module.function_definitions()[0].type_(), type_);
+        assert_eq!(
+            module.function_definitions()[0]
+                .arguments()
+                .iter()
+                .map(|argument| argument.name())
+                .collect::<Vec<_>>(),
+            vec!["argc", "argv"]
+        );
+        assert_eq!(crate::analysis::validation::validate(&module), Ok(()));
+    }
+
+    #[test]
+    fn define_function_with_cleanup() {
+        let builder = ModuleBuilder::new();
+        let pointer = build::variable("p", types::generic_pointer_type());
+
+        builder
+            .define_function_with_cleanup(
+                "f",
+                vec![
+                    Argument::new("p", types::generic_pointer_type()),
+                    Argument::new("x", types::Primitive::Boolean),
+                ],
+                types::void_type(),
+                |builder| {
+                    builder.if_(
+                        build::variable("x", types::Primitive::Boolean),
+                        |builder| Ok(builder.return_(void_value())),
+                        |builder| Ok(builder.branch(void_value())),
+                    )?;
+
+                    Ok::<_, BuildError>(builder.return_(void_value()))
+                },
+                |builder| {
+                    builder.free_heap(pointer.clone());
+
+                    Ok(())
+                },
+                Default::default(),
+            )
+            .unwrap();
+
+        let module = builder.into_module();
+        let body = module.function_definitions()[0].body();
+        let cleanup = FreeHeap::new(Variable::new("p")).into();
+
+        assert_eq!(body.instructions().len(), 2);
+        assert_eq!(body.instructions()[1], cleanup);
+
+        if let Instruction::If(if_) = &body.instructions()[0] {
+            assert_eq!(if_.then().instructions(), &[cleanup]);
+            assert!(if_.else_().instructions().is_empty());
+        } else {
+            unreachable!()
+        }
+
+        assert_eq!(crate::analysis::validation::validate(&module), Ok(()));
+    }
+
+    mod split_at {
+        use super::*;
+        use crate::analysis::{name, validation};
+        use pretty_assertions::assert_eq;
+
+        fn define_functions(builder: &ModuleBuilder, count: usize) {
+            let function_type = types::Function::new(
+                vec![],
+                types::Primitive::PointerInteger,
+                types::CallingConvention::Source,
+            );
+
+            for index in 0..count {
+                let function_type = function_type.clone();
+
+                builder
+                    .define_function(
+                        format!("f{}", index),
+                        vec![],
+                        types::Primitive::PointerInteger,
+                        |builder| {
+                            Ok::<_, BuildError>(if index == 0 {
+                                builder.return_(builder.load(build::variable(
+                                    "x",
+                                    types::Pointer::new(types::Primitive::PointerInteger),
+                                ))?)
+                            } else {
+                                builder.return_(builder.call(
+                                    build::variable(
+                                        format!("f{}", index - 1),
+                                        function_type.clone(),
+                                    ),
+                                    Vec::<TypedExpression>::new(),
+                                )?)
+                            })
+                        },
+                        FunctionDefinitionOptions::new(),
+                    )
+                    .unwrap();
+            }
+        }
+
+        #[test]
+        fn split_functions() {
+            let builder = ModuleBuilder::new();
+
+            builder.define_variable(
+                "x",
+                Primitive::PointerInteger(42),
+                VariableDefinitionOptions::new(),
+            );
+            define_functions(&builder, 10);
+
+            let modules = builder.split_at(4);
+
+            assert_eq!(
+                modules
+                    .iter()
+                    .map(|module| module.function_definitions().len())
+                    .collect::<Vec<_>>(),
+                vec![4, 4, 2]
+            );
+
+            for module in &modules {
+                assert_eq!(
+                    module.function_declarations().len(),
+                    10 - module.function_definitions().len()
+                );
+                assert_eq!(validation::validate(module), Ok(()));
+            }
+
+            assert_eq!(modules[0].variable_definitions().len(), 1);
+            assert_eq!(
+                modules[1].variable_declarations(),
+                &[VariableDeclaration::new(
+                    "x",
+                    types::Primitive::PointerInteger
+                )]
+            );
+            assert!(modules[1]
+                .function_declarations()
+                .iter()
+                .any(|declaration| declaration.name() == "f3"));
+        }
+
+        #[test]
+        fn export_internal_definitions_referenced_across_modules() {
+            let builder = ModuleBuilder::new();
+            let string = builder.define_c_string(b"foo");
+            let function_type = types::Function::new(
+                vec![],
+                types::Primitive::Integer8,
+                types::CallingConvention::Source,
+            );
+
+            builder
+                .define_function(
+                    "f",
+                    vec![],
+                    types::Primitive::Integer8,
+                    |builder| Ok::<_, BuildError>(builder.return_(Primitive::Integer8(0))),
+                    FunctionDefinitionOptions::new().set_linkage(Linkage::Internal),
+                )
+                .unwrap();
+            builder
+                .define_function(
+                    "g",
+                    vec![],
+                    types::Primitive::Integer8,
+                    |builder| {
+                        builder.load(string.clone())?;
+
+                        Ok::<_, BuildError>(builder.return_(builder.call(
+                            build::variable("f", function_type.clone()),
+                            Vec::<TypedExpression>::new(),
+                        )?))
+                    },
+                    FunctionDefinitionOptions::new(),
+                )
+                .unwrap();
+
+            let modules = builder.split_at(1);
+
+            assert_eq!(
+                modules[0].variable_definitions()[0].options().linkage(),
+                Linkage::External
+            );
+            assert_eq!(
+                modules[0].function_definitions()[0].options().linkage(),
+                Linkage::External
+            );
+
+            for module in &modules {
+                assert_eq!(validation::validate(module), Ok(()));
+            }
+        }
+
+        #[test]
+        fn keep_internal_definitions_referenced_in_same_module() {
+            let builder = ModuleBuilder::new();
+            let string = builder.define_c_string(b"foo");
+
+            builder
+                .define_function(
+                    "f",
+                    vec![],
+                    types::Primitive::Integer8,
+                    |builder| Ok::<_, BuildError>(builder.return_(builder.load(string.clone())?)),
+                    FunctionDefinitionOptions::new(),
+                )
+                .unwrap();
+
+            let modules = builder.split_at(1);
+
+            assert_eq!(
+                modules[0].variable_definitions()[0].options().linkage(),
+                Linkage::Internal
+            );
+        }
+
+        #[test]
+        fn split_functions_declared_already() {
+            let builder = ModuleBuilder::new();
+
+            builder.define_variable(
+                "x",
+                Primitive::PointerInteger(42),
+                VariableDefinitionOptions::new(),
+            );
+            builder.declare_variable("x", types::Primitive::PointerInteger);
+            define_functions(&builder, 2);
+            builder.declare_function(
+                "f1",
+                types::Function::new(
+                    vec![],
+                    types::Primitive::PointerInteger,
+                    types::CallingConvention::Source,
+                ),
+            );
+
+            let modules = builder.split_at(1);
+
+            assert_eq!(modules[0].function_declarations().len(), 1);
+            assert_eq!(modules[1].function_declarations().len(), 2);
+            assert_eq!(modules[1].variable_declarations().len(), 1);
+
+            for module in &modules {
+                assert_eq!(name::check(module), Ok(()));
+                assert_eq!(validation::validate(module), Ok(()));
+            }
+        }
+
+        #[test]
+        fn split_empty_builder() {
+            assert_eq!(
+                ModuleBuilder::new().split_at(4),
+                vec![Module::new(vec![], vec![], vec![], vec![])]
+            );
+        }
+    }
+
+    mod export_as_c {
+        use super::*;
+        use crate::analysis::{c_calling_convention, type_check};
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn export_function_with_large_record() {
+            let builder = ModuleBuilder::new();
+            let record_type = types::Record::new(vec![types::Primitive::Integer64.into(); 4]);
+
+            let function = builder
+                .define_function(
+                    "f",
+                    vec![Argument::new("x", record_type.clone())],
+                    record_type.clone(),
+                    |builder| {
+                        Ok::<_, BuildError>(
+                            builder.return_(build::variable("x", record_type.clone())),
+                        )
+                    },
+                    FunctionDefinitionOptions::new(),
+                )
+                .unwrap();
+
+            let wrapper = builder.export_as_c(function, "g").unwrap();
+
+            assert_eq!(
+                wrapper,
+                build::variable(
+                    "g",
+                    types::Function::new(
+                        vec![record_type.clone().into()],
+                        record_type.clone(),
+                        types::CallingConvention::Target,
+                    )
+                )
+            );
+
+            let mut module = builder.into_module();
+
+            type_check::check(&module).unwrap();
+            c_calling_convention::transform(&mut module, 8).unwrap();
+            type_check::check(&module).unwrap();
+
+            let definition = &module.function_definitions()[1];
+
+            assert_eq!(definition.name(), "g");
+            assert!(definition.options().has_struct_return_argument());
+            assert_eq!(
+                definition
+                    .arguments()
+                    .iter()
+                    .map(|argument| argument.type_().clone())
+                    .collect::<Vec<_>>(),
+                vec![
+                    types::Pointer::new(record_type.clone()).into(),
+                    types::Pointer::new(record_type).into()
+                ]
+            );
+        }
+
+        #[test]
+        fn fail_to_export_non_function() {
+            assert_eq!(
+                ModuleBuilder::new()
+                    .export_as_c(build::variable("x", types::Primitive::PointerInteger), "g"),
+                Err(BuildError::FunctionExpected(
+                    types::Primitive::PointerInteger.into()
+                ))
+            );
+        }
+    }
+
+    mod c_string {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn define_c_string() {
+            let builder = ModuleBuilder::new();
+
+            let string = builder.define_c_string(b"foo");
+
+            assert_eq!(
+                string.type_(),
+                &types::Pointer::new(types::Primitive::Integer8).into()
+            );
+
+            let module = builder.into_module();
+
+            assert_eq!(
+                module.variable_definitions()[0].body(),
+                &Record::new(
+                    types::Record::new(vec![types::Primitive::Integer8.into(); 4]),
+                    vec![
+                        Primitive::Integer8(b'f').into(),
+                        Primitive::Integer8(b'o').into(),
+                        Primitive::Integer8(b'o').into(),
+                        Primitive::Integer8(0).into(),
+                    ]
+                )
+                .into()
+            );
+            assert_eq!(crate::analysis::validation::validate(&module), Ok(()));
+        }
+
+        #[test]
+        fn share_identical_c_strings() {
+            let builder = ModuleBuilder::new();
+
+            let string = builder.define_c_string(b"foo");
+
+            assert_eq!(builder.define_c_string(b"foo"), string);
+            assert_ne!(builder.define_c_string(b"bar"), string);
+            assert_eq!(builder.into_module().variable_definitions().len(), 2);
+        }
+    }
+}
@@ -24,6 +24,16 @@ impl TypedExpression {
     pub fn type_(&self) -> &Type {
         &self.type_
     }
+
+    pub fn as_function(&self) -> Option<&types::Function> {
+        self.type_.to_function()
+    }
+}
+
+impl<E: Into<Expression>, T: Into<Type>> From<(E, T)> for TypedExpression {
+    fn from((expression, type_): (E, T)) -> Self {
+        Self::new(expression, type_)
+    }
 }
 
 impl From<AlignOf> for TypedExpression {
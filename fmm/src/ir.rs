@@ -25,7 +25,9 @@ mod function_declaration;
 mod function_definition;
 mod function_definition_options;
 mod if_;
+mod if_branch;
 mod instruction;
+mod instruction_path;
 mod linkage;
 mod load;
 mod memory_copy;
@@ -38,6 +40,7 @@ mod record_address;
 mod return_;
 mod size_of;
 mod store;
+mod tail_call;
 mod terminal_instruction;
 mod undefined;
 mod union;
@@ -75,7 +78,9 @@ pub use function_declaration::*;
 pub use function_definition::*;
 pub use function_definition_options::*;
 pub use if_::*;
+pub use if_branch::*;
 pub use instruction::*;
+pub use instruction_path::*;
 pub use linkage::*;
 pub use load::*;
 pub use memory_copy::*;
@@ -88,6 +93,7 @@ pub use record_address::*;
 pub use return_::*;
 pub use size_of::*;
 pub use store::*;
+pub use tail_call::*;
 pub use terminal_instruction::*;
 pub use undefined::*;
 pub use union::*;
@@ -1,11 +1,13 @@
+use super::expression::Expression;
 use crate::types::Type;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AllocateStack(Box<AllocateStackInner>);
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 struct AllocateStackInner {
     type_: Type,
+    count: Option<Expression>,
     name: String,
 }
 
@@ -14,6 +16,23 @@ impl AllocateStack {
         Self(
             AllocateStackInner {
                 type_: type_.into(),
+                count: None,
+                name: name.into(),
+            }
+            .into(),
+        )
+    }
+
+    // Allocates an array of a dynamic number of elements.
+    pub fn array(
+        type_: impl Into<Type>,
+        count: impl Into<Expression>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self(
+            AllocateStackInner {
+                type_: type_.into(),
+                count: Some(count.into()),
                 name: name.into(),
             }
             .into(),
@@ -28,6 +47,14 @@ impl AllocateStack {
         &mut self.0.type_
     }
 
+    pub fn count(&self) -> Option<&Expression> {
+        self.0.count.as_ref()
+    }
+
+    pub fn count_mut(&mut self) -> Option<&mut Expression> {
+        self.0.count.as_mut()
+    }
+
     pub fn name(&self) -> &str {
         &self.0.name
     }
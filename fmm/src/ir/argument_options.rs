@@ -1,11 +1,17 @@
+use crate::types::Type;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ArgumentOptions {
     alias: bool,
+    original_type: Option<Type>,
 }
 
 impl ArgumentOptions {
     pub fn new() -> Self {
-        Self { alias: true }
+        Self {
+            alias: true,
+            original_type: None,
+        }
     }
 
     pub fn alias(&self) -> bool {
@@ -13,7 +19,20 @@ impl ArgumentOptions {
     }
 
     pub fn set_alias(self, alias: bool) -> Self {
-        Self { alias }
+        Self { alias, ..self }
+    }
+
+    // Logical types of arguments before they are lowered for C calling
+    // conventions
+    pub fn original_type(&self) -> Option<&Type> {
+        self.original_type.as_ref()
+    }
+
+    pub fn set_original_type(self, original_type: Option<Type>) -> Self {
+        Self {
+            original_type,
+            ..self
+        }
     }
 }
 
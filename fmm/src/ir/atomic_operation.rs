@@ -18,6 +18,7 @@ struct AtomicOperationInner {
     value: Expression,
     ordering: AtomicOrdering,
     name: String,
+    result_discarded: bool,
 }
 
 impl AtomicOperation {
@@ -37,11 +38,26 @@ impl AtomicOperation {
                 value: value.into(),
                 ordering,
                 name: name.into(),
+                result_discarded: false,
             }
             .into(),
         )
     }
 
+    pub fn without_result(
+        type_: types::Primitive,
+        operator: AtomicOperator,
+        pointer: impl Into<Expression>,
+        value: impl Into<Expression>,
+        ordering: AtomicOrdering,
+    ) -> Self {
+        let mut operation = Self::new(type_, operator, pointer, value, ordering, "");
+
+        operation.0.result_discarded = true;
+
+        operation
+    }
+
     pub fn type_(&self) -> types::Primitive {
         self.0.type_
     }
@@ -77,4 +93,8 @@ impl AtomicOperation {
     pub fn name(&self) -> &str {
         &self.0.name
     }
+
+    pub fn is_result_discarded(&self) -> bool {
+        self.0.result_discarded
+    }
 }
@@ -32,4 +32,89 @@ impl Block {
     pub fn terminal_instruction_mut(&mut self) -> &mut TerminalInstruction {
         &mut self.terminal_instruction
     }
+
+    // Terminal instructions are not counted.
+    pub fn instruction_count(&self, recursive: bool) -> usize {
+        self.instructions.len()
+            + if recursive {
+                self.instructions
+                    .iter()
+                    .map(|instruction| match instruction {
+                        Instruction::If(if_) => {
+                            if_.then().instruction_count(true) + if_.else_().instruction_count(true)
+                        }
+                        _ => 0,
+                    })
+                    .sum()
+            } else {
+                0
+            }
+    }
+
+    pub fn max_if_depth(&self) -> usize {
+        self.instructions
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::If(if_) => {
+                    1 + if_.then().max_if_depth().max(if_.else_().max_if_depth())
+                }
+                _ => 0,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::*,
+        types::{self, void_type},
+    };
+
+    fn create_block() -> Block {
+        Block::new(
+            vec![
+                AllocateStack::new(types::Primitive::PointerInteger, "x").into(),
+                If::new(
+                    void_type(),
+                    Primitive::Boolean(true),
+                    Block::new(
+                        vec![
+                            AllocateStack::new(types::Primitive::PointerInteger, "y").into(),
+                            If::new(
+                                void_type(),
+                                Primitive::Boolean(true),
+                                Block::new(vec![], Branch::new(void_type(), void_value())),
+                                Block::new(vec![], Branch::new(void_type(), void_value())),
+                                "z",
+                            )
+                            .into(),
+                        ],
+                        Branch::new(void_type(), void_value()),
+                    ),
+                    Block::new(vec![], Branch::new(void_type(), void_value())),
+                    "w",
+                )
+                .into(),
+            ],
+            Return::new(void_type(), void_value()),
+        )
+    }
+
+    #[test]
+    fn count_instructions() {
+        assert_eq!(create_block().instruction_count(false), 2);
+        assert_eq!(create_block().instruction_count(true), 4);
+    }
+
+    #[test]
+    fn calculate_max_if_depth() {
+        assert_eq!(
+            Block::new(vec![], Return::new(void_type(), void_value())).max_if_depth(),
+            0
+        );
+        assert_eq!(create_block().max_if_depth(), 2);
+    }
 }
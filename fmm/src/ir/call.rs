@@ -1,4 +1,4 @@
-use super::expression::Expression;
+use super::{expression::Expression, tail_call::TailCall};
 use crate::types;
 use std::rc::Rc;
 
@@ -12,6 +12,7 @@ struct CallInner {
     arguments: Vec<Expression>,
     name: String,
     environment: Vec<Rc<str>>,
+    tail_call: TailCall,
 }
 
 impl Call {
@@ -28,6 +29,7 @@ impl Call {
                 arguments,
                 name: name.into(),
                 environment: Default::default(),
+                tail_call: Default::default(),
             }
             .into(),
         )
@@ -72,4 +74,13 @@ impl Call {
     pub fn environment_mut(&mut self) -> &mut Vec<Rc<str>> {
         &mut self.0.environment
     }
+
+    pub fn tail_call(&self) -> TailCall {
+        self.0.tail_call
+    }
+
+    pub fn set_tail_call(mut self, tail_call: TailCall) -> Self {
+        self.0.tail_call = tail_call;
+        self
+    }
 }
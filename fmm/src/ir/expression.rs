@@ -5,6 +5,7 @@ use super::{
     primitive::Primitive, record::Record, record_address::RecordAddress, size_of::SizeOf,
     undefined::Undefined, union::Union, union_address::UnionAddress, variable::Variable,
 };
+use crate::types::{self, Type};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Expression {
@@ -25,6 +26,82 @@ pub enum Expression {
     Variable(Variable),
 }
 
+impl Expression {
+    // Operands are not checked against types of operations. Use the type check
+    // pass to validate them.
+    pub fn type_of(&self, environment: &impl Fn(&str) -> Option<Type>) -> Option<Type> {
+        Some(match self {
+            Self::AlignOf(_) => AlignOf::RESULT_TYPE.into(),
+            Self::ArithmeticOperation(operation) => operation.type_().into(),
+            Self::BitCast(bit_cast) => bit_cast.to().clone(),
+            Self::BitwiseNotOperation(operation) => operation.type_().into(),
+            Self::BitwiseOperation(operation) => operation.type_().into(),
+            Self::ComparisonOperation(_) => ComparisonOperation::RESULT_TYPE.into(),
+            Self::PointerAddress(address) => address.type_().clone().into(),
+            Self::Primitive(primitive) => primitive.type_().into(),
+            Self::Record(record) => record.type_().clone().into(),
+            Self::RecordAddress(address) => {
+                types::Pointer::new(address.type_().fields().get(address.field_index())?.clone())
+                    .into()
+            }
+            Self::SizeOf(_) => SizeOf::RESULT_TYPE.into(),
+            Self::Undefined(undefined) => undefined.type_().clone(),
+            Self::Union(union) => union.type_().clone().into(),
+            Self::UnionAddress(address) => types::Pointer::new(
+                address
+                    .type_()
+                    .members()
+                    .get(address.member_index())?
+                    .clone(),
+            )
+            .into(),
+            Self::Variable(variable) => environment(variable.name())?,
+        })
+    }
+
+    // Variables are listed as many times as they are referenced.
+    pub fn referenced_variables(&self) -> Vec<&str> {
+        let mut names = vec![];
+
+        self.collect_variables(&mut names);
+
+        names
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Self::ArithmeticOperation(operation) => {
+                operation.lhs().collect_variables(names);
+                operation.rhs().collect_variables(names);
+            }
+            Self::BitCast(bit_cast) => bit_cast.expression().collect_variables(names),
+            Self::BitwiseNotOperation(operation) => operation.value().collect_variables(names),
+            Self::BitwiseOperation(operation) => {
+                operation.lhs().collect_variables(names);
+                operation.rhs().collect_variables(names);
+            }
+            Self::ComparisonOperation(operation) => {
+                operation.lhs().collect_variables(names);
+                operation.rhs().collect_variables(names);
+            }
+            Self::PointerAddress(address) => {
+                address.pointer().collect_variables(names);
+                address.offset().collect_variables(names);
+            }
+            Self::Record(record) => {
+                for field in record.fields() {
+                    field.collect_variables(names);
+                }
+            }
+            Self::RecordAddress(address) => address.pointer().collect_variables(names),
+            Self::Union(union) => union.member().collect_variables(names),
+            Self::UnionAddress(address) => address.pointer().collect_variables(names),
+            Self::Variable(variable) => names.push(variable.name()),
+            Self::AlignOf(_) | Self::Primitive(_) | Self::SizeOf(_) | Self::Undefined(_) => {}
+        }
+    }
+}
+
 impl From<AlignOf> for Expression {
     fn from(align_of: AlignOf) -> Self {
         Self::AlignOf(align_of)
@@ -118,10 +195,98 @@ impl From<Variable> for Expression {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ir::ArithmeticOperator;
+    use pretty_assertions::assert_eq;
     use std::mem::size_of;
 
     #[test]
     fn size() {
         assert!(size_of::<Expression>() <= 3 * size_of::<usize>());
     }
+
+    #[test]
+    fn collect_referenced_variables_in_record() {
+        assert_eq!(
+            Expression::from(Record::new(
+                types::Record::new(vec![
+                    types::Primitive::PointerInteger.into(),
+                    types::Primitive::PointerInteger.into(),
+                    types::Primitive::PointerInteger.into(),
+                ]),
+                vec![
+                    Variable::new("x").into(),
+                    Primitive::PointerInteger(42).into(),
+                    ArithmeticOperation::new(
+                        types::Primitive::PointerInteger,
+                        ArithmeticOperator::Add,
+                        Variable::new("y"),
+                        Primitive::PointerInteger(1),
+                    )
+                    .into(),
+                ],
+            ))
+            .referenced_variables(),
+            vec!["x", "y"]
+        );
+    }
+
+    #[test]
+    fn resolve_type_of_variable() {
+        let environment = |name: &str| {
+            if name == "x" {
+                Some(types::Primitive::Float64.into())
+            } else {
+                None
+            }
+        };
+
+        assert_eq!(
+            Expression::from(Variable::new("x")).type_of(&environment),
+            Some(types::Primitive::Float64.into())
+        );
+        assert_eq!(
+            Expression::from(Variable::new("y")).type_of(&environment),
+            None
+        );
+    }
+
+    #[test]
+    fn resolve_type_of_record() {
+        let record_type = types::Record::new(vec![
+            types::Primitive::Float64.into(),
+            types::Primitive::PointerInteger.into(),
+        ]);
+
+        assert_eq!(
+            Expression::from(Record::new(
+                record_type.clone(),
+                vec![
+                    Variable::new("x").into(),
+                    Primitive::PointerInteger(42).into()
+                ],
+            ))
+            .type_of(&|_| None),
+            Some(record_type.into())
+        );
+    }
+
+    #[test]
+    fn resolve_type_of_record_address() {
+        let record_type = types::Record::new(vec![types::Primitive::Float64.into()]);
+
+        assert_eq!(
+            Expression::from(RecordAddress::new(
+                record_type.clone(),
+                Variable::new("x"),
+                0
+            ))
+            .type_of(&|_| None),
+            Some(types::Pointer::new(types::Primitive::Float64).into())
+        );
+        assert_eq!(
+            Expression::from(RecordAddress::new(record_type, Variable::new("x"), 1))
+                .type_of(&|_| None),
+            None
+        );
+    }
 }
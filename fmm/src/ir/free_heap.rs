@@ -1,4 +1,5 @@
 use super::expression::Expression;
+use crate::types::Type;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FreeHeap(Box<FreeHeapInner>);
@@ -6,6 +7,7 @@ pub struct FreeHeap(Box<FreeHeapInner>);
 #[derive(Clone, Debug, PartialEq)]
 struct FreeHeapInner {
     pointer: Expression,
+    type_: Option<Type>,
 }
 
 impl FreeHeap {
@@ -13,6 +15,19 @@ impl FreeHeap {
         Self(
             FreeHeapInner {
                 pointer: pointer.into(),
+                type_: None,
+            }
+            .into(),
+        )
+    }
+
+    // Sized frees are for allocators which need sizes and alignments of freed
+    // values.
+    pub fn new_sized(type_: impl Into<Type>, pointer: impl Into<Expression>) -> Self {
+        Self(
+            FreeHeapInner {
+                pointer: pointer.into(),
+                type_: Some(type_.into()),
             }
             .into(),
         )
@@ -25,4 +40,12 @@ impl FreeHeap {
     pub fn pointer_mut(&mut self) -> &mut Expression {
         &mut self.0.pointer
     }
+
+    pub fn type_(&self) -> Option<&Type> {
+        self.0.type_.as_ref()
+    }
+
+    pub fn type_mut(&mut self) -> Option<&mut Type> {
+        self.0.type_.as_mut()
+    }
 }
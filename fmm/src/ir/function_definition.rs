@@ -46,6 +46,14 @@ impl FunctionDefinition {
         &mut self.arguments
     }
 
+    pub fn struct_return_argument(&self) -> Option<&Argument> {
+        if self.options.has_struct_return_argument() {
+            self.arguments.first()
+        } else {
+            None
+        }
+    }
+
     pub fn body(&self) -> &Block {
         &self.body
     }
@@ -99,3 +107,28 @@ impl PartialEq for FunctionDefinition {
             && self.options == other.options
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{void_value, Return};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn type_ignores_argument_names() {
+        let create_definition = |name| {
+            FunctionDefinition::new(
+                "f",
+                vec![Argument::new(name, types::Primitive::PointerInteger)],
+                types::void_type(),
+                Block::new(vec![], Return::new(types::void_type(), void_value())),
+                Default::default(),
+            )
+        };
+
+        assert_eq!(
+            create_definition("x").type_(),
+            create_definition("y").type_()
+        );
+    }
+}
@@ -4,19 +4,32 @@ use crate::types::CallingConvention;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FunctionDefinitionOptions {
     address_named: bool,
+    alignment: Option<usize>,
     calling_convention: CallingConvention,
+    leaf: bool,
     linkage: Linkage,
+    pure: bool,
+    struct_return_argument: bool,
 }
 
 impl FunctionDefinitionOptions {
     pub fn new() -> Self {
         Self {
             address_named: true,
+            alignment: None,
             calling_convention: CallingConvention::Source,
+            leaf: false,
             linkage: Linkage::External,
+            pure: false,
+            struct_return_argument: false,
         }
     }
 
+    // Alignments are in bytes and must be powers of two.
+    pub fn alignment(&self) -> Option<usize> {
+        self.alignment
+    }
+
     pub fn calling_convention(&self) -> CallingConvention {
         self.calling_convention
     }
@@ -25,10 +38,27 @@ impl FunctionDefinitionOptions {
         self.address_named
     }
 
+    // Leaf functions do not call any functions.
+    pub fn is_leaf(&self) -> bool {
+        self.leaf
+    }
+
     pub fn linkage(&self) -> Linkage {
         self.linkage
     }
 
+    // Pure functions have no side effects and return the same results for the
+    // same arguments.
+    pub fn is_pure(&self) -> bool {
+        self.pure
+    }
+
+    // A struct return argument is a pointer to a result prepended to
+    // arguments by the C calling convention transformation.
+    pub fn has_struct_return_argument(&self) -> bool {
+        self.struct_return_argument
+    }
+
     pub fn set_address_named(self, address_named: bool) -> Self {
         Self {
             address_named,
@@ -36,6 +66,10 @@ impl FunctionDefinitionOptions {
         }
     }
 
+    pub fn set_alignment(self, alignment: Option<usize>) -> Self {
+        Self { alignment, ..self }
+    }
+
     pub fn set_calling_convention(self, calling_convention: CallingConvention) -> Self {
         Self {
             calling_convention,
@@ -43,9 +77,24 @@ impl FunctionDefinitionOptions {
         }
     }
 
+    pub fn set_leaf(self, leaf: bool) -> Self {
+        Self { leaf, ..self }
+    }
+
     pub fn set_linkage(self, linkage: Linkage) -> Self {
         Self { linkage, ..self }
     }
+
+    pub fn set_pure(self, pure: bool) -> Self {
+        Self { pure, ..self }
+    }
+
+    pub fn set_struct_return_argument(self, struct_return_argument: bool) -> Self {
+        Self {
+            struct_return_argument,
+            ..self
+        }
+    }
 }
 
 impl Default for FunctionDefinitionOptions {
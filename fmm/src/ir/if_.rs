@@ -1,4 +1,4 @@
-use super::{block::Block, expression::Expression};
+use super::{block::Block, expression::Expression, if_branch::IfBranch};
 use crate::types::Type;
 use std::rc::Rc;
 
@@ -12,6 +12,7 @@ struct IfInner {
     then: Block,
     else_: Block,
     name: String,
+    likely: Option<IfBranch>,
     environment: Vec<Rc<str>>,
 }
 
@@ -30,6 +31,7 @@ impl If {
                 then,
                 else_,
                 name: name.into(),
+                likely: None,
                 environment: Default::default(),
             }
             .into(),
@@ -76,6 +78,15 @@ impl If {
         &mut self.0.name
     }
 
+    pub fn likely(&self) -> Option<IfBranch> {
+        self.0.likely
+    }
+
+    pub fn set_likely(mut self, likely: Option<IfBranch>) -> Self {
+        self.0.likely = likely;
+        self
+    }
+
     pub fn environment(&self) -> &[Rc<str>] {
         &self.0.environment
     }
@@ -0,0 +1,5 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IfBranch {
+    Then,
+    Else,
+}
@@ -3,7 +3,7 @@ use super::{
     atomic_operation::AtomicOperation, atomic_store::AtomicStore, call::Call,
     compare_and_swap::CompareAndSwap, deconstruct_record::DeconstructRecord,
     deconstruct_union::DeconstructUnion, fence::Fence, free_heap::FreeHeap, if_::If, load::Load,
-    reallocate_heap::ReallocateHeap, store::Store, MemoryCopy,
+    reallocate_heap::ReallocateHeap, store::Store, Expression, MemoryCopy,
 };
 use crate::types::{self, generic_pointer_type, Type};
 
@@ -36,16 +36,32 @@ impl Instruction {
                 types::Pointer::new(allocate.type_().clone()).into(),
             )),
             Self::AtomicLoad(load) => Some((load.name(), load.type_().clone())),
-            Self::AtomicOperation(operation) => Some((operation.name(), operation.type_().into())),
+            Self::AtomicOperation(operation) => {
+                if operation.is_result_discarded() {
+                    None
+                } else {
+                    Some((operation.name(), operation.type_().into()))
+                }
+            }
             Self::Call(call) => Some((call.name(), call.type_().result().clone())),
             Self::CompareAndSwap(cas) => Some((cas.name(), types::Primitive::Boolean.into())),
+            // Malformed instructions with out-of-range indices are reported by
+            // type checking.
             Self::DeconstructRecord(deconstruct) => Some((
                 deconstruct.name(),
-                deconstruct.type_().fields()[deconstruct.field_index()].clone(),
+                deconstruct
+                    .type_()
+                    .fields()
+                    .get(deconstruct.field_index())?
+                    .clone(),
             )),
             Self::DeconstructUnion(deconstruct) => Some((
                 deconstruct.name(),
-                deconstruct.type_().members()[deconstruct.member_index()].clone(),
+                deconstruct
+                    .type_()
+                    .members()
+                    .get(deconstruct.member_index())?
+                    .clone(),
             )),
             Self::If(if_) => Some((if_.name(), if_.type_().clone())),
             Self::Load(load) => Some((load.name(), load.type_().clone())),
@@ -55,6 +71,91 @@ impl Instruction {
         }
     }
 
+    // Blocks of if instructions are not included.
+    pub fn operands(&self) -> Vec<&Expression> {
+        match self {
+            Self::AllocateHeap(allocate) => vec![allocate.size()],
+            Self::AllocateStack(allocate) => allocate.count().into_iter().collect(),
+            Self::Fence(_) => vec![],
+            Self::AtomicLoad(load) => vec![load.pointer()],
+            Self::AtomicOperation(operation) => vec![operation.pointer(), operation.value()],
+            Self::AtomicStore(store) => vec![store.value(), store.pointer()],
+            Self::Call(call) => [call.function()]
+                .into_iter()
+                .chain(call.arguments())
+                .collect(),
+            Self::CompareAndSwap(cas) => vec![cas.pointer(), cas.old_value(), cas.new_value()],
+            Self::DeconstructRecord(deconstruct) => vec![deconstruct.record()],
+            Self::DeconstructUnion(deconstruct) => vec![deconstruct.union()],
+            Self::FreeHeap(free) => vec![free.pointer()],
+            Self::If(if_) => vec![if_.condition()],
+            Self::Load(load) => vec![load.pointer()],
+            Self::MemoryCopy(copy) => vec![copy.source(), copy.destination(), copy.size()],
+            Self::ReallocateHeap(reallocate) => vec![reallocate.pointer(), reallocate.size()],
+            Self::Store(store) => vec![store.value(), store.pointer()],
+        }
+    }
+
+    // Blocks of if instructions are not rewritten.
+    pub fn map_operands(&self, map: impl Fn(&Expression) -> Expression) -> Self {
+        let mut instruction = self.clone();
+
+        match &mut instruction {
+            Self::AllocateHeap(allocate) => *allocate.size_mut() = map(allocate.size()),
+            Self::AllocateStack(allocate) => {
+                if let Some(count) = allocate.count_mut() {
+                    *count = map(count);
+                }
+            }
+            Self::Fence(_) => {}
+            Self::AtomicLoad(load) => *load.pointer_mut() = map(load.pointer()),
+            Self::AtomicOperation(operation) => {
+                *operation.pointer_mut() = map(operation.pointer());
+                *operation.value_mut() = map(operation.value());
+            }
+            Self::AtomicStore(store) => {
+                *store.value_mut() = map(store.value());
+                *store.pointer_mut() = map(store.pointer());
+            }
+            Self::Call(call) => {
+                *call.function_mut() = map(call.function());
+
+                for argument in call.arguments_mut() {
+                    *argument = map(argument);
+                }
+            }
+            Self::CompareAndSwap(cas) => {
+                *cas.pointer_mut() = map(cas.pointer());
+                *cas.old_value_mut() = map(cas.old_value());
+                *cas.new_value_mut() = map(cas.new_value());
+            }
+            Self::DeconstructRecord(deconstruct) => {
+                *deconstruct.record_mut() = map(deconstruct.record())
+            }
+            Self::DeconstructUnion(deconstruct) => {
+                *deconstruct.union_mut() = map(deconstruct.union())
+            }
+            Self::FreeHeap(free) => *free.pointer_mut() = map(free.pointer()),
+            Self::If(if_) => *if_.condition_mut() = map(if_.condition()),
+            Self::Load(load) => *load.pointer_mut() = map(load.pointer()),
+            Self::MemoryCopy(copy) => {
+                *copy.source_mut() = map(copy.source());
+                *copy.destination_mut() = map(copy.destination());
+                *copy.size_mut() = map(copy.size());
+            }
+            Self::ReallocateHeap(reallocate) => {
+                *reallocate.pointer_mut() = map(reallocate.pointer());
+                *reallocate.size_mut() = map(reallocate.size());
+            }
+            Self::Store(store) => {
+                *store.value_mut() = map(store.value());
+                *store.pointer_mut() = map(store.pointer());
+            }
+        }
+
+        instruction
+    }
+
     pub fn has_blocks(&self) -> bool {
         matches!(self, Instruction::If(_))
     }
@@ -159,10 +260,141 @@ impl From<Store> for Instruction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ir::{AtomicOperator, AtomicOrdering, Block, Branch, Primitive, Variable};
+    use pretty_assertions::assert_eq;
     use std::mem::size_of;
 
     #[test]
     fn type_size() {
         assert!(size_of::<Type>() <= 2 * size_of::<usize>());
     }
+
+    #[test]
+    fn value_of_atomic_operation() {
+        assert_eq!(
+            Instruction::from(AtomicOperation::new(
+                types::Primitive::PointerInteger,
+                AtomicOperator::Add,
+                Variable::new("x"),
+                Primitive::PointerInteger(1),
+                AtomicOrdering::Relaxed,
+                "y",
+            ))
+            .value(),
+            Some(("y", types::Primitive::PointerInteger.into()))
+        );
+    }
+
+    #[test]
+    fn value_of_atomic_operation_without_result() {
+        assert_eq!(
+            Instruction::from(AtomicOperation::without_result(
+                types::Primitive::PointerInteger,
+                AtomicOperator::Add,
+                Variable::new("x"),
+                Primitive::PointerInteger(1),
+                AtomicOrdering::Relaxed,
+            ))
+            .value(),
+            None
+        );
+    }
+
+    #[test]
+    fn operands_of_store() {
+        assert_eq!(
+            Instruction::from(Store::new(
+                types::Primitive::PointerInteger,
+                Primitive::PointerInteger(42),
+                Variable::new("x"),
+            ))
+            .operands(),
+            vec![
+                &Primitive::PointerInteger(42).into(),
+                &Variable::new("x").into()
+            ]
+        );
+    }
+
+    #[test]
+    fn operands_of_call() {
+        assert_eq!(
+            Instruction::from(Call::new(
+                types::Function::new(
+                    vec![types::Primitive::PointerInteger.into()],
+                    types::void_type(),
+                    types::CallingConvention::Target,
+                ),
+                Variable::new("f"),
+                vec![Variable::new("x").into()],
+                "y",
+            ))
+            .operands(),
+            vec![&Variable::new("f").into(), &Variable::new("x").into()]
+        );
+    }
+
+    #[test]
+    fn map_operands_of_call() {
+        let function_type = types::Function::new(
+            vec![
+                types::Primitive::PointerInteger.into(),
+                types::Primitive::PointerInteger.into(),
+            ],
+            types::void_type(),
+            types::CallingConvention::Target,
+        );
+
+        assert_eq!(
+            Instruction::from(Call::new(
+                function_type.clone(),
+                Variable::new("f"),
+                vec![Variable::new("x").into(), Variable::new("x").into()],
+                "y",
+            ))
+            .map_operands(|expression| match expression {
+                Expression::Variable(variable) if variable.name() == "x" => {
+                    Primitive::PointerInteger(42).into()
+                }
+                _ => expression.clone(),
+            }),
+            Call::new(
+                function_type,
+                Variable::new("f"),
+                vec![
+                    Primitive::PointerInteger(42).into(),
+                    Primitive::PointerInteger(42).into()
+                ],
+                "y",
+            )
+            .into()
+        );
+    }
+
+    #[test]
+    fn map_operands_of_if_without_blocks() {
+        let block = Block::new(
+            vec![],
+            Branch::new(types::Primitive::Boolean, Variable::new("x")),
+        );
+
+        assert_eq!(
+            Instruction::from(If::new(
+                types::Primitive::Boolean,
+                Variable::new("x"),
+                block.clone(),
+                block.clone(),
+                "y",
+            ))
+            .map_operands(|_| Primitive::Boolean(true).into()),
+            If::new(
+                types::Primitive::Boolean,
+                Primitive::Boolean(true),
+                block.clone(),
+                block,
+                "y",
+            )
+            .into()
+        );
+    }
 }
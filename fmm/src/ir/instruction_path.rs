@@ -0,0 +1,36 @@
+use super::if_branch::IfBranch;
+
+// Paths consist of indices of if instructions with their branches from function
+// bodies down to blocks, and indices of instructions in the blocks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstructionPath {
+    function_name: String,
+    branches: Vec<(usize, IfBranch)>,
+    index: usize,
+}
+
+impl InstructionPath {
+    pub fn new(
+        function_name: impl Into<String>,
+        branches: Vec<(usize, IfBranch)>,
+        index: usize,
+    ) -> Self {
+        Self {
+            function_name: function_name.into(),
+            branches,
+            index,
+        }
+    }
+
+    pub fn function_name(&self) -> &str {
+        &self.function_name
+    }
+
+    pub fn branches(&self) -> &[(usize, IfBranch)] {
+        &self.branches
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
@@ -9,6 +9,8 @@ struct LoadInner {
     type_: Type, // pointer element type
     pointer: Expression,
     name: String,
+    alignment: Option<usize>,
+    dereferenceable_bytes: Option<usize>,
 }
 
 impl Load {
@@ -22,6 +24,8 @@ impl Load {
                 type_: type_.into(),
                 pointer: pointer.into(),
                 name: name.into(),
+                alignment: None,
+                dereferenceable_bytes: None,
             }
             .into(),
         )
@@ -46,4 +50,24 @@ impl Load {
     pub fn name(&self) -> &str {
         &self.0.name
     }
+
+    // Natural alignments of types are used by default.
+    pub fn alignment(&self) -> Option<usize> {
+        self.0.alignment
+    }
+
+    pub fn set_alignment(mut self, alignment: Option<usize>) -> Self {
+        self.0.alignment = alignment;
+        self
+    }
+
+    // Sizes of types are used by default.
+    pub fn dereferenceable_bytes(&self) -> Option<usize> {
+        self.0.dereferenceable_bytes
+    }
+
+    pub fn set_dereferenceable_bytes(mut self, bytes: Option<usize>) -> Self {
+        self.0.dereferenceable_bytes = bytes;
+        self
+    }
 }
@@ -1,5 +1,6 @@
 use super::{
     function_declaration::FunctionDeclaration, function_definition::FunctionDefinition,
+    if_branch::IfBranch, instruction::Instruction, instruction_path::InstructionPath,
     variable_declaration::VariableDeclaration, variable_definition::VariableDefinition,
 };
 
@@ -57,4 +58,173 @@ impl Module {
     pub fn function_definitions_mut(&mut self) -> &mut Vec<FunctionDefinition> {
         &mut self.function_definitions
     }
+
+    pub fn instruction_at(&self, path: &InstructionPath) -> Option<&Instruction> {
+        let mut block = self
+            .function_definitions
+            .iter()
+            .find(|definition| definition.name() == path.function_name())?
+            .body();
+
+        for (index, branch) in path.branches() {
+            if let Instruction::If(if_) = block.instructions().get(*index)? {
+                block = match branch {
+                    IfBranch::Then => if_.then(),
+                    IfBranch::Else => if_.else_(),
+                };
+            } else {
+                return None;
+            }
+        }
+
+        block.instructions().get(path.index())
+    }
+
+    // Sorts declarations and definitions by names for deterministic outputs.
+    pub fn sorted(&self) -> Self {
+        let mut module = self.clone();
+
+        module
+            .variable_declarations
+            .sort_by(|one, other| one.name().cmp(other.name()));
+        module
+            .function_declarations
+            .sort_by(|one, other| one.name().cmp(other.name()));
+        module
+            .variable_definitions
+            .sort_by(|one, other| one.name().cmp(other.name()));
+        module
+            .function_definitions
+            .sort_by(|one, other| one.name().cmp(other.name()));
+
+        module
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::*,
+        types::{self, void_type},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn create_module(names: &[&str]) -> Module {
+        Module::new(
+            names
+                .iter()
+                .map(|name| VariableDeclaration::new(format!("{}_x", name), void_type()))
+                .collect(),
+            names
+                .iter()
+                .map(|name| {
+                    FunctionDeclaration::new(
+                        format!("{}_f", name),
+                        types::Function::new(vec![], void_type(), types::CallingConvention::Source),
+                    )
+                })
+                .collect(),
+            names
+                .iter()
+                .map(|name| {
+                    VariableDefinition::new(
+                        format!("{}_y", name),
+                        void_value(),
+                        void_type(),
+                        Default::default(),
+                    )
+                })
+                .collect(),
+            names
+                .iter()
+                .map(|name| {
+                    FunctionDefinition::new(
+                        format!("{}_g", name),
+                        vec![],
+                        void_type(),
+                        Block::new(vec![], Return::new(void_type(), void_value())),
+                        Default::default(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn sort_module() {
+        assert_eq!(
+            create_module(&["c", "a", "b"]).sorted(),
+            create_module(&["a", "b", "c"])
+        );
+        assert_eq!(
+            create_module(&["b", "c", "a"]).sorted(),
+            create_module(&["c", "a", "b"]).sorted()
+        );
+    }
+
+    #[test]
+    fn find_instruction_in_if() {
+        let instruction = Instruction::from(Load::new(
+            types::Primitive::PointerInteger,
+            Variable::new("p"),
+            "y",
+        ));
+        let module = Module::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![FunctionDefinition::new(
+                "f",
+                vec![],
+                void_type(),
+                Block::new(
+                    vec![If::new(
+                        void_type(),
+                        Primitive::Boolean(true),
+                        Block::new(vec![], Branch::new(void_type(), void_value())),
+                        Block::new(
+                            vec![If::new(
+                                void_type(),
+                                Primitive::Boolean(false),
+                                Block::new(
+                                    vec![
+                                        AllocateStack::new(types::Primitive::PointerInteger, "p")
+                                            .into(),
+                                        instruction.clone(),
+                                    ],
+                                    Branch::new(void_type(), void_value()),
+                                ),
+                                Block::new(vec![], Branch::new(void_type(), void_value())),
+                                "z",
+                            )
+                            .into()],
+                            Branch::new(void_type(), void_value()),
+                        ),
+                        "x",
+                    )
+                    .into()],
+                    Return::new(void_type(), void_value()),
+                ),
+                Default::default(),
+            )],
+        );
+
+        assert_eq!(
+            module.instruction_at(&InstructionPath::new(
+                "f",
+                vec![(0, IfBranch::Else), (0, IfBranch::Then)],
+                1
+            )),
+            Some(&instruction)
+        );
+        assert_eq!(
+            module.instruction_at(&InstructionPath::new("f", vec![(0, IfBranch::Then)], 0)),
+            None
+        );
+        assert_eq!(
+            module.instruction_at(&InstructionPath::new("g", vec![], 0)),
+            None
+        );
+    }
 }
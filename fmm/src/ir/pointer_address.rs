@@ -10,7 +10,7 @@ struct PointerAddressInner {
     // TODO Change this to an element type?
     type_: types::Pointer, // type of the pointer value
     pointer: Expression,
-    offset: Expression,
+    offset: Expression, // in elements like GEP in LLVM, not in bytes
 }
 
 impl PointerAddress {
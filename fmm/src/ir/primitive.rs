@@ -14,6 +14,24 @@ pub enum Primitive {
 }
 
 impl Primitive {
+    // Checked constructors accept both signed and unsigned interpretations of
+    // a literal and store negative values in two's complement.
+    pub fn integer8_checked(value: i64) -> Option<Self> {
+        if (i8::MIN as i64..=u8::MAX as i64).contains(&value) {
+            Some(Self::Integer8(value as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn integer32_checked(value: i64) -> Option<Self> {
+        if (i32::MIN as i64..=u32::MAX as i64).contains(&value) {
+            Some(Self::Integer32(value as u32))
+        } else {
+            None
+        }
+    }
+
     pub fn type_(&self) -> types::Primitive {
         match self {
             Primitive::Boolean(_) => types::Primitive::Boolean,
@@ -50,3 +68,39 @@ impl From<i64> for Primitive {
         Self::PointerInteger(number)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_integer8() {
+        assert_eq!(
+            Primitive::integer8_checked(42),
+            Some(Primitive::Integer8(42))
+        );
+        assert_eq!(
+            Primitive::integer8_checked(200),
+            Some(Primitive::Integer8(200))
+        );
+        assert_eq!(
+            Primitive::integer8_checked(-1),
+            Some(Primitive::Integer8(255))
+        );
+        assert_eq!(Primitive::integer8_checked(300), None);
+        assert_eq!(Primitive::integer8_checked(-129), None);
+    }
+
+    #[test]
+    fn check_integer32() {
+        assert_eq!(
+            Primitive::integer32_checked(u32::MAX as i64),
+            Some(Primitive::Integer32(u32::MAX))
+        );
+        assert_eq!(
+            Primitive::integer32_checked(i32::MIN as i64),
+            Some(Primitive::Integer32(1 << 31))
+        );
+        assert_eq!(Primitive::integer32_checked(1 << 32), None);
+    }
+}
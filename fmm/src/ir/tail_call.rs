@@ -0,0 +1,9 @@
+// All calls are hinted as tail calls to backends. Must-tail calls are
+// guaranteed to reuse stack frames of their callers and backends reject them
+// if they cannot guarantee that.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TailCall {
+    #[default]
+    Hint,
+    MustTail,
+}
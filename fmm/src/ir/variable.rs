@@ -1,5 +1,7 @@
 use std::rc::Rc;
 
+// Variables of global variables are pointers to them while ones of functions
+// are function values which can be stored and called as function pointers.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Variable {
     name: Rc<str>,
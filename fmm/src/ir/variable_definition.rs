@@ -40,6 +40,12 @@ impl VariableDefinition {
         &self.options
     }
 
+    // Initializers referring to global symbols need relocations when they are
+    // position-independent.
+    pub fn referenced_symbols(&self) -> Vec<&str> {
+        self.body.referenced_variables()
+    }
+
     pub fn body_mut(&mut self) -> &mut Expression {
         &mut self.body
     }
@@ -48,3 +54,47 @@ impl VariableDefinition {
         &mut self.type_
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::{Primitive, Record, Variable},
+        types::{self, void_type},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn collect_referenced_symbols_of_function_addresses() {
+        let function_type =
+            types::Function::new(vec![], void_type(), types::CallingConvention::Target);
+        let record_type =
+            types::Record::new(vec![function_type.clone().into(), function_type.into()]);
+
+        assert_eq!(
+            VariableDefinition::new(
+                "x",
+                Record::new(
+                    record_type.clone(),
+                    vec![Variable::new("f").into(), Variable::new("g").into()],
+                ),
+                record_type,
+                Default::default(),
+            )
+            .referenced_symbols(),
+            vec!["f", "g"]
+        );
+    }
+
+    #[test]
+    fn collect_no_referenced_symbol_of_constant() {
+        assert!(VariableDefinition::new(
+            "x",
+            Primitive::PointerInteger(42),
+            types::Primitive::PointerInteger,
+            Default::default(),
+        )
+        .referenced_symbols()
+        .is_empty());
+    }
+}
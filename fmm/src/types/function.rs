@@ -45,6 +45,10 @@ impl Function {
         &self.0.arguments
     }
 
+    pub fn arity(&self) -> usize {
+        self.0.arguments.len()
+    }
+
     pub fn result(&self) -> &Type {
         &self.0.result
     }
@@ -10,3 +10,72 @@ pub enum Primitive {
     Integer64,
     PointerInteger,
 }
+
+impl Primitive {
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::Float32 | Self::Float64)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Self::Integer8 | Self::Integer32 | Self::Integer64 | Self::PointerInteger
+        )
+    }
+
+    pub fn is_pointer_sized(&self) -> bool {
+        matches!(self, Self::PointerInteger)
+    }
+
+    pub fn byte_width(&self, word_bytes: usize) -> usize {
+        match self {
+            Self::Boolean | Self::Integer8 => 1,
+            Self::Float32 | Self::Integer32 => 4,
+            Self::Float64 | Self::Integer64 => 8,
+            Self::PointerInteger => word_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify() {
+        for (primitive, float, integer) in [
+            (Primitive::Boolean, false, false),
+            (Primitive::Float32, true, false),
+            (Primitive::Float64, true, false),
+            (Primitive::Integer8, false, true),
+            (Primitive::Integer32, false, true),
+            (Primitive::Integer64, false, true),
+            (Primitive::PointerInteger, false, true),
+        ] {
+            assert_eq!(primitive.is_float(), float);
+            assert_eq!(primitive.is_integer(), integer);
+            assert_eq!(
+                primitive.is_pointer_sized(),
+                primitive == Primitive::PointerInteger
+            );
+        }
+    }
+
+    #[test]
+    fn byte_width() {
+        for (primitive, width) in [
+            (Primitive::Boolean, 1),
+            (Primitive::Float32, 4),
+            (Primitive::Float64, 8),
+            (Primitive::Integer8, 1),
+            (Primitive::Integer32, 4),
+            (Primitive::Integer64, 8),
+        ] {
+            assert_eq!(primitive.byte_width(4), width);
+            assert_eq!(primitive.byte_width(8), width);
+        }
+
+        assert_eq!(Primitive::PointerInteger.byte_width(4), 4);
+        assert_eq!(Primitive::PointerInteger.byte_width(8), 8);
+    }
+}
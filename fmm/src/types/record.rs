@@ -32,6 +32,10 @@ impl Record {
     pub fn fields(&self) -> &[Type] {
         &self.0.fields
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.fields.is_empty()
+    }
 }
 
 impl PartialEq for Record {
@@ -74,4 +78,11 @@ mod tests {
         assert_eq!(hash(&record), hash(&record));
         assert_ne!(hash(&record), hash(&Record::new(vec![])));
     }
+
+    #[test]
+    fn check_empty() {
+        assert!(Record::new(vec![]).is_empty());
+        assert!(!Record::new(vec![Record::new(vec![]).into()]).is_empty());
+        assert!(!Record::new(vec![Primitive::PointerInteger.into()]).is_empty());
+    }
 }
@@ -16,3 +16,44 @@ pub fn generic_pointer_type() -> Type {
 pub fn void_type() -> types::Record {
     VOID_TYPE.with(|record| (*record).clone())
 }
+
+pub fn pointer_depth(type_: &Type) -> usize {
+    match type_ {
+        Type::Pointer(pointer) => 1 + pointer_depth(pointer.element()),
+        _ => 0,
+    }
+}
+
+pub fn strip_pointers(type_: &Type) -> &Type {
+    match type_ {
+        Type::Pointer(pointer) => strip_pointers(pointer.element()),
+        _ => type_,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_pointer() -> Type {
+        Pointer::new(Pointer::new(Pointer::new(Primitive::Integer64))).into()
+    }
+
+    #[test]
+    fn calculate_pointer_depth() {
+        assert_eq!(pointer_depth(&Primitive::Integer64.into()), 0);
+        assert_eq!(pointer_depth(&create_pointer()), 3);
+    }
+
+    #[test]
+    fn strip_nested_pointers() {
+        assert_eq!(
+            strip_pointers(&Primitive::Integer64.into()),
+            &Primitive::Integer64.into()
+        );
+        assert_eq!(
+            strip_pointers(&create_pointer()),
+            &Primitive::Integer64.into()
+        );
+    }
+}